@@ -0,0 +1,147 @@
+use crate::data::*;
+use crate::entities;
+use rand_pcg::Pcg64;
+use specs::{Entity, World};
+
+/// A boss floor layout
+/// '#' = Wall, '.' = Floor, '@' = Player start, 'B' = Boss, 'a' = Add spawn point, 'S' = Staircase, 'v' = Fire vent
+pub struct ArenaTemplate {
+    pub layout: &'static [&'static str],
+    pub boss: Option<fn(Position, &mut World) -> Entity>, // None = Random boss
+}
+
+pub const ARENA_TEMPLATES: [ArenaTemplate; 4] = [
+    ArenaTemplate {
+        layout: &[
+            "     #######     ",
+            "   ###.....###   ",
+            "  ##....S....##  ",
+            " ##...........## ",
+            " #.a.........a.# ",
+            "##.............##",
+            "#.......B.......#",
+            "#...............#",
+            "#...............#",
+            "#.a...........a.#",
+            "#.......@.......#",
+            "##.............##",
+            " #.............# ",
+            " ##...........## ",
+            "  ##.........##  ",
+            "   ###.....###   ",
+            "     #######     ",
+        ],
+        boss: None,
+    },
+    ArenaTemplate {
+        layout: &[
+            "###################",
+            "#.a......S......a.#",
+            "#.................#",
+            "#..#...#...#...#..#",
+            "#.................#",
+            "#.................#",
+            "#..#.....B.....#..#",
+            "#.................#",
+            "#.................#",
+            "#..#...#...#...#..#",
+            "#.................#",
+            "#.................#",
+            "#..#...#...#...#..#",
+            "#.................#",
+            "#.a......@......a.#",
+            "###################",
+        ],
+        boss: Some(entities::create_xilphene_the_moth_priestess),
+    },
+    ArenaTemplate {
+        layout: &[
+            "        #######        ",
+            "        #..S..#        ",
+            "        #.....#        ",
+            "        #.....#        ",
+            "        #.....#        ",
+            "#########.....#########",
+            "#a...................a#",
+            "#..........B..........#",
+            "#.....................#",
+            "#########.....#########",
+            "        #.....#        ",
+            "        #.....#        ",
+            "        #a...a#        ",
+            "        #.....#        ",
+            "        #..@..#        ",
+            "        #######        ",
+        ],
+        boss: Some(entities::create_ume_the_dungeon_heart),
+    },
+    ArenaTemplate {
+        layout: &[
+            "  #################  ",
+            " ##.......S.......## ",
+            "##..v...........v..##",
+            "#...................#",
+            "#.......v...v.......#",
+            "#..a.............a..#",
+            "#.........B.........#",
+            "#...................#",
+            "#..v.............v..#",
+            "#.......v...v.......#",
+            "#...................#",
+            "##..a...........a..##",
+            " ##.......@.......## ",
+            "  #################  ",
+        ],
+        boss: Some(entities::create_siro_king_of_hell),
+    },
+];
+
+/// Builds the arena around the player's starting position at (0, 0), spawns the boss and inserts the BossArena resource
+pub fn stamp_arena(template: &ArenaTemplate, world: &mut World, rng: &mut Pcg64) {
+    let (player_x, player_y) = template
+        .layout
+        .iter()
+        .enumerate()
+        .find_map(|(row, line)| line.find('@').map(|column| (column as i16, row as i16)))
+        .unwrap();
+
+    let mut boss_position = None;
+    let mut add_spawn_positions = Vec::new();
+    let mut staircase_position = None;
+    for (row, line) in template.layout.iter().enumerate() {
+        for (column, tile) in line.chars().enumerate() {
+            let position = Position::new(column as i16 - player_x, player_y - row as i16);
+            match tile {
+                '#' => {
+                    entities::create_wall(position, world, rng);
+                }
+                ' ' => {}
+                _ => {
+                    entities::create_floor(position, world);
+                }
+            }
+            match tile {
+                'B' => boss_position = Some(position),
+                'a' => add_spawn_positions.push(position),
+                'S' => staircase_position = Some(position),
+                'v' => {
+                    entities::create_fire_vent(position, world);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    match template.boss {
+        Some(create_boss) => {
+            (create_boss)(boss_position.unwrap(), world);
+        }
+        None => {
+            entities::create_random_layer1(Rarity::Epic, boss_position.unwrap(), world);
+        }
+    }
+    world.insert(BossArena {
+        add_spawn_positions,
+        staircase_position: staircase_position.unwrap(),
+    });
+}
@@ -1,107 +1,69 @@
 use crate::data::*;
 use crate::entities;
+use crate::status_effects::has_status;
+use crate::triggers::run_triggers;
+use rand::Rng;
 use specs::{Entity, Join, World, WorldExt};
+use std::collections::HashSet;
 
-/// Returns whether or not the target died and the amount of damage dealt
+/// Returns whether or not the target died, the amount of damage dealt,
+/// and how the target's resistances changed it
 pub fn damage(
-    mut damage: u32,
+    damage: u32,
     is_melee: bool,
-    is_magic: bool,
+    damage_type: DamageType,
     attacker: Option<Entity>,
     target: Entity,
     world: &mut World,
-) -> (bool, u32) {
-    let target_died = {
-        let mut attackable_data = world.write_storage::<Attackable>();
+) -> (bool, u32, Option<Resistance>) {
+    let (resistance, is_unharmed) = {
+        let attackable_data = world.read_storage::<Attackable>();
         let target_attackable = attackable_data.get(target).unwrap();
-        if target_attackable.current_health == 0 || (is_magic && target_attackable.is_magic_immune)
-        {
-            return (false, 0);
-        }
-
-        if let Some(attacker) = attacker {
-            let attacker_attackable = attackable_data.get(attacker).unwrap().clone();
-            let target_attackable = attackable_data.get_mut(target).unwrap();
-
-            if attacker_attackable.is_oozing {
-                damage += target_attackable.oozed_stacks;
-                if is_melee {
-                    target_attackable.oozed_stacks += 1;
-                }
-            }
-        }
+        let resistance = target_attackable.resistances.get(&damage_type).copied();
+        (
+            resistance,
+            target_attackable.current_health == 0
+                || target_attackable.is_invulnerable
+                || resistance == Some(Resistance::Immune),
+        )
+    };
+    if is_unharmed {
+        return (false, 0, resistance);
+    }
 
-        {
-            let target_attackable = attackable_data.get(target).unwrap();
-            let (lower_spawn_times_health_threshold, lower_spawn_times_by_turns) =
-                target_attackable.lower_spawn_times;
-            if lower_spawn_times_health_threshold != 0.0
-                && lower_spawn_times_by_turns != 0
-                && target_attackable.current_health as f32 / target_attackable.max_health as f32
-                    <= lower_spawn_times_health_threshold
-            {
-                {
-                    let mut spawner_data = world.write_storage::<Spawner>();
-                    for spawner in (&mut spawner_data).join() {
-                        spawner.turns_per_spawn = spawner
-                            .turns_per_spawn
-                            .saturating_sub(lower_spawn_times_by_turns);
-                        if spawner.turns_per_spawn < 10 {
-                            spawner.turns_per_spawn = 10;
-                        }
-                    }
-                    let mut message_log = world.fetch_mut::<MessageLog>();
-                    message_log.new_message(
-                        "The air around you feels more... dangerous...",
-                        MessageColor::White,
-                        MessageDisplayLength::Medium,
-                    );
-                }
-            }
-        }
+    let mut hit = Hit {
+        attacker,
+        target,
+        damage,
+        is_melee,
+        damage_type,
+    };
+    if let Some(attacker) = attacker {
+        run_triggers(TriggerEvent::Attack, attacker, &mut hit, world);
+    }
+    run_triggers(TriggerEvent::Struck, target, &mut hit, world);
+    if let Some(resistance) = resistance {
+        hit.damage = resistance.scale(hit.damage);
+    }
 
+    let target_died = {
+        let mut attackable_data = world.write_storage::<Attackable>();
         let target_attackable = attackable_data.get_mut(target).unwrap();
-        target_attackable.current_health = target_attackable.current_health.saturating_sub(damage);
-
+        target_attackable.current_health =
+            target_attackable.current_health.saturating_sub(hit.damage);
         target_attackable.current_health == 0
     };
 
-    if target_died {
-        let (blast_damage, blast_radius) = {
-            let attackable_data = world.read_storage::<Attackable>();
-            let target_attackable = attackable_data.get(target).unwrap();
-            target_attackable.explode_on_death
-        };
-        if blast_damage != 0 && blast_radius != 0 {
-            {
-                let mut message_log = world.fetch_mut::<MessageLog>();
-                let name_data = world.read_storage::<Name>();
-                let target_name = name_data.get(target).unwrap();
-                message_log.new_message(
-                    format!("{} exploded!", target_name.get_text()),
-                    MessageColor::White,
-                    MessageDisplayLength::Medium,
-                );
-            }
+    if let Some(attacker) = attacker {
+        run_triggers(TriggerEvent::Hit, attacker, &mut hit, world);
+    }
+    run_triggers(TriggerEvent::Damaged, target, &mut hit, world);
 
-            let targets = {
-                let attackable_data = world.read_storage::<Attackable>();
-                let position_data = world.read_storage::<Position>();
-                let entities = world.entities();
-                let target_position = position_data.get(target).unwrap();
-                (&entities, &position_data, &attackable_data)
-                    .join()
-                    .filter(|(_, position, _)| {
-                        (position.x - target_position.x).abs() <= blast_radius as i16
-                            && (position.y - target_position.y).abs() <= blast_radius as i16
-                    })
-                    .map(|(entity, _, _)| entity)
-                    .collect::<Vec<Entity>>()
-            };
-            for target in targets {
-                self::damage(blast_damage, false, false, Some(target), target, world);
-            }
+    if target_died {
+        if let Some(attacker) = attacker {
+            run_triggers(TriggerEvent::Kill, attacker, &mut hit, world);
         }
+        run_triggers(TriggerEvent::Death, target, &mut hit, world);
 
         let target_is_boss = {
             let attackable_data = world.read_storage::<Attackable>();
@@ -121,11 +83,14 @@ pub fn damage(
                 player_attackable.current_health = player_attackable.max_health;
             }
             {
-                let target_position = {
-                    let position_data = world.read_storage::<Position>();
-                    *position_data.get(target).unwrap()
+                let staircase_position = match world.try_fetch::<BossArena>() {
+                    Some(boss_arena) => boss_arena.staircase_position,
+                    None => {
+                        let position_data = world.read_storage::<Position>();
+                        *position_data.get(target).unwrap()
+                    }
                 };
-                entities::create_staircase(target_position, world);
+                entities::create_staircase(staircase_position, world);
             }
         }
 
@@ -151,33 +116,69 @@ pub fn damage(
         world.delete_entity(target).unwrap();
     }
 
-    (target_died, damage)
+    (target_died, hit.damage, resistance)
 }
 
-/// Ok(bool) means the attack went through and whether or not the target died from it
+/// Ok(AttackResult) means the attack went through, and whether it missed, hit, or killed the target
 /// Err(()) means the attacker was unable to attack
+#[allow(clippy::too_many_arguments)]
 pub fn try_attack(
     base_damage: u32,
     is_melee: bool,
-    is_magic: bool,
-    minimum_range: u32,
-    maximum_range: u32,
+    damage_type: DamageType,
+    critical_hit: Option<(u32, u32)>,
+    targeting: Targeting,
     attacker: Entity,
     target: Entity,
     world: &mut World,
-) -> Result<bool, ()> {
-    if can_attack(minimum_range, maximum_range, attacker, target, world) {
-        let (attacker_name, target_name) = {
+) -> Result<AttackResult, ()> {
+    if can_attack(targeting, attacker, target, world) {
+        let (attacker_name, target_name, hit_chance) = {
             let name_data = world.read_storage::<Name>();
+            let attackable_data = world.read_storage::<Attackable>();
+            let hit_chance = attackable_data
+                .get(attacker)
+                .unwrap()
+                .accuracy
+                .saturating_sub(attackable_data.get(target).unwrap().evasion);
             (
                 *name_data.get(attacker).unwrap(),
                 *name_data.get(target).unwrap(),
+                hit_chance,
             )
         };
-        let (target_died, damage_dealt) = damage(
+
+        let (is_hit, is_critical_hit) = {
+            let rng = &mut world.fetch_mut::<RNG>().0;
+            // Only roll when it could go either way, so attacks that can't miss leave the RNG alone
+            let is_hit = hit_chance >= 100 || rng.gen_ratio(hit_chance, 100);
+            let is_critical_hit = match critical_hit {
+                Some((chance, _)) if is_hit && chance != 0 => rng.gen_ratio(chance.min(100), 100),
+                _ => false,
+            };
+            (is_hit, is_critical_hit)
+        };
+        if !is_hit {
+            world.fetch_mut::<MessageLog>().new_message(
+                format!(
+                    "{} attacked {} but missed",
+                    attacker_name.get_text(),
+                    target_name.get_text(),
+                ),
+                MessageColor::White,
+                MessageDisplayLength::Short,
+            );
+            return Ok(AttackResult::Missed);
+        }
+
+        let base_damage = match critical_hit {
+            Some((_, multiplier)) if is_critical_hit => base_damage * multiplier,
+            _ => base_damage,
+        };
+        let (target_died, damage_dealt, resistance) = damage(
             base_damage,
             is_melee,
-            is_magic,
+            damage_type,
             Some(attacker),
             target,
             world,
@@ -186,81 +187,235 @@ pub fn try_attack(
         let mut message_log = world.fetch_mut::<MessageLog>();
         message_log.new_message(
             format!(
-                "{} attacked {} for {} damage",
+                "{} {} {} for {} damage{}",
                 attacker_name.get_text(),
+                if is_critical_hit {
+                    "critically hit"
+                } else {
+                    "attacked"
+                },
                 target_name.get_text(),
                 damage_dealt,
+                resistance.map_or("", Resistance::describe),
             ),
-            MessageColor::White,
+            if is_critical_hit {
+                MessageColor::Orange
+            } else {
+                MessageColor::White
+            },
             MessageDisplayLength::Short,
         );
 
-        Ok(target_died)
+        if target_died {
+            Ok(AttackResult::Killed)
+        } else {
+            Ok(AttackResult::Hit)
+        }
     } else {
         Err(())
     }
 }
 
-pub fn can_attack(
-    minimum_range: u32,
-    maximum_range: u32,
-    attacker: Entity,
-    target: Entity,
-    world: &World,
-) -> bool {
+pub fn can_attack(targeting: Targeting, attacker: Entity, target: Entity, world: &World) -> bool {
     let position_data = world.read_storage::<Position>();
     let attackable_data = world.read_storage::<Attackable>();
-    let attacker_position = position_data.get(attacker).unwrap();
-    let attacker_attackable = attackable_data.get(attacker).unwrap();
-    let target_position = position_data.get(target).unwrap();
-
-    if attacker_attackable.cant_attack_turns != 0 {
+    let footprint_data = world.read_storage::<Footprint>();
+    // The attacker can die before attacking, like when it moves onto a trap first
+    if !attackable_data.contains(attacker) {
+        return false;
+    }
+    if has_status(attacker, StatusKind::CantAttack, world) {
         return false;
     }
 
-    let x_gap = (attacker_position.x - target_position.x).abs() as u32;
-    let y_gap = (attacker_position.y - target_position.y).abs() as u32;
+    let attacker_positions = position_data
+        .get(attacker)
+        .unwrap()
+        .footprint_positions(footprint_data.get(attacker));
+    let target_positions = position_data
+        .get(target)
+        .unwrap()
+        .footprint_positions(footprint_data.get(target));
+    attacker_positions.iter().any(|attacker_position| {
+        target_positions.iter().any(|target_position| {
+            is_in_range(targeting, *attacker_position, *target_position)
+                && has_line_of_sight(
+                    targeting.line_of_sight,
+                    *attacker_position,
+                    *target_position,
+                    [attacker, target],
+                    world,
+                )
+        })
+    })
+}
+
+fn is_in_range(
+    targeting: Targeting,
+    attacker_position: Position,
+    target_position: Position,
+) -> bool {
+    let x_gap = (attacker_position.x - target_position.x).unsigned_abs() as u32;
+    let y_gap = (attacker_position.y - target_position.y).unsigned_abs() as u32;
     let is_straight_path = x_gap == 0 || y_gap == 0;
-    let gap = x_gap + y_gap;
+    let is_diagonal_path = x_gap == y_gap;
+    let gap = x_gap.max(y_gap);
 
-    is_straight_path && gap >= minimum_range && gap <= maximum_range
+    (is_straight_path || (targeting.allows_diagonal && is_diagonal_path))
+        && gap >= targeting.minimum_range
+        && gap <= targeting.maximum_range
 }
 
-pub fn player_get_target(minimum_range: u32, maximum_range: u32, world: &World) -> Option<Entity> {
+/// Checks the positions strictly between start and end, which must be in a straight or diagonal line
+/// The attacker and target never block themselves
+fn has_line_of_sight(
+    line_of_sight: LineOfSight,
+    start: Position,
+    end: Position,
+    ignored_entities: [Entity; 2],
+    world: &World,
+) -> bool {
+    let x_step = (end.x - start.x).signum();
+    let y_step = (end.y - start.y).signum();
+    let mut positions_between = HashSet::new();
+    let mut position = Position::new(start.x + x_step, start.y + y_step);
+    while position != end {
+        positions_between.insert(position);
+        position = Position::new(position.x + x_step, position.y + y_step);
+    }
+    if positions_between.is_empty() {
+        return true;
+    }
+
     let entities = world.entities();
-    let player_data = world.read_storage::<Player>();
     let position_data = world.read_storage::<Position>();
     let intangible_data = world.read_storage::<Intangible>();
     let attackable_data = world.read_storage::<Attackable>();
+    let footprint_data = world.read_storage::<Footprint>();
+    !(
+        &entities,
+        &position_data,
+        !&intangible_data,
+        attackable_data.maybe(),
+        footprint_data.maybe(),
+    )
+        .join()
+        .any(|(entity, position, _, attackable, footprint)| {
+            !ignored_entities.contains(&entity)
+                && (attackable.is_none() || line_of_sight == LineOfSight::FirstEntity)
+                && position
+                    .footprint_positions(footprint)
+                    .iter()
+                    .any(|position| positions_between.contains(position))
+        })
+}
+
+/// Returns the target selected by the player if it's in range and in the direction they're facing,
+/// otherwise the closest target in the direction they're facing
+pub fn player_get_target(targeting: Targeting, world: &World) -> Option<Entity> {
+    let player_data = world.read_storage::<Player>();
+    let position_data = world.read_storage::<Position>();
+    let footprint_data = world.read_storage::<Footprint>();
     let (player, player_position) = (&player_data, &position_data).join().next().unwrap();
 
-    for range in minimum_range..=maximum_range {
-        let mut offset = match player.facing_direction {
-            Direction::Up => Position::new(0, 1),
-            Direction::Down => Position::new(0, -1),
-            Direction::Left => Position::new(-1, 0),
-            Direction::Right => Position::new(1, 0),
-            _ => Position::new(0, 0),
-        };
-        offset.x *= range as i16;
-        offset.y *= range as i16;
-        let target_position =
-            Position::new(player_position.x + offset.x, player_position.y + offset.y);
+    let offset = match player.facing_direction {
+        Direction::Up => Position::new(0, 1),
+        Direction::Down => Position::new(0, -1),
+        Direction::Left => Position::new(-1, 0),
+        Direction::Right => Position::new(1, 0),
+        Direction::UpLeft => Position::new(-1, 1),
+        Direction::DownLeft => Position::new(-1, -1),
+        Direction::DownRight => Position::new(1, -1),
+        Direction::UpRight => Position::new(1, 1),
+    };
+    let is_diagonal = offset.x != 0 && offset.y != 0;
+    if is_diagonal && !targeting.allows_diagonal {
+        return None;
+    }
 
-        let target_entity = (
-            &entities,
-            &position_data,
-            !&intangible_data,
-            &attackable_data,
-            !&player_data,
-        )
-            .join()
-            .find(|(_, position, _, _, _)| position == &&target_position)
-            .map(|(entity, _, _, _, _)| entity);
-        if target_entity.is_some() {
-            return target_entity;
+    let targets = player_get_targets(targeting, world);
+    let targets_at_range = |range: u32| {
+        let target_position = Position::new(
+            player_position.x + offset.x * range as i16,
+            player_position.y + offset.y * range as i16,
+        );
+        targets
+            .iter()
+            .filter(|target| {
+                position_data
+                    .get(**target)
+                    .unwrap()
+                    .footprint_positions(footprint_data.get(**target))
+                    .contains(&target_position)
+            })
+            .copied()
+            .collect::<Vec<Entity>>()
+    };
+
+    if let Some(selected_target) = player.selected_target {
+        for range in targeting.minimum_range..=targeting.maximum_range {
+            if targets_at_range(range).contains(&selected_target) {
+                return Some(selected_target);
+            }
+        }
+    }
+    for range in targeting.minimum_range..=targeting.maximum_range {
+        if let Some(target_entity) = targets_at_range(range).first() {
+            return Some(*target_entity);
         }
     }
 
     None
 }
+
+/// Returns every entity the player could target, closest first
+pub fn player_get_targets(targeting: Targeting, world: &World) -> Vec<Entity> {
+    let entities = world.entities();
+    let player_data = world.read_storage::<Player>();
+    let position_data = world.read_storage::<Position>();
+    let intangible_data = world.read_storage::<Intangible>();
+    let attackable_data = world.read_storage::<Attackable>();
+    let footprint_data = world.read_storage::<Footprint>();
+    let (player_entity, _, player_position) = (&entities, &player_data, &position_data)
+        .join()
+        .next()
+        .unwrap();
+
+    let mut targets = Vec::new();
+    for (entity, position, _, _, _, footprint) in (
+        &entities,
+        &position_data,
+        !&intangible_data,
+        &attackable_data,
+        !&player_data,
+        footprint_data.maybe(),
+    )
+        .join()
+    {
+        let closest_position = position
+            .footprint_positions(footprint)
+            .into_iter()
+            .filter(|position| {
+                is_in_range(targeting, *player_position, *position)
+                    && has_line_of_sight(
+                        targeting.line_of_sight,
+                        *player_position,
+                        *position,
+                        [player_entity, entity],
+                        world,
+                    )
+            })
+            .min_by_key(|position| position.chebyshev_distance_from(*player_position));
+        if let Some(closest_position) = closest_position {
+            targets.push((entity, closest_position));
+        }
+    }
+    targets.sort_unstable_by_key(|(_, position)| {
+        (
+            position.chebyshev_distance_from(*player_position),
+            position.x,
+            position.y,
+        )
+    });
+    targets.into_iter().map(|(entity, _)| entity).collect()
+}
@@ -5,7 +5,7 @@ use rand_pcg::Pcg64;
 use specs::storage::BTreeStorage;
 use specs::{Component, Entity, World};
 use specs_derive::Component;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
 
 #[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
@@ -45,6 +45,10 @@ impl Position {
         (other.x - self.x).abs() as u32 + (other.y - self.y).abs() as u32
     }
 
+    pub fn chebyshev_distance_from(self, other: Self) -> u32 {
+        ((other.x - self.x).unsigned_abs() as u32).max((other.y - self.y).unsigned_abs() as u32)
+    }
+
     pub fn offset_by(self, direction: Direction) -> Self {
         match direction {
             Direction::Up => Self::new(self.x, self.y + 1),
@@ -58,6 +62,18 @@ impl Position {
         }
     }
 
+    /// Returns every position taken up by an entity at this position with the given footprint
+    pub fn footprint_positions(self, footprint: Option<&Footprint>) -> Vec<Position> {
+        let radius = footprint.map_or(0, |footprint| footprint.radius);
+        let mut positions = Vec::with_capacity(((radius * 2 + 1) * (radius * 2 + 1)) as usize);
+        for x in -radius..=radius {
+            for y in -radius..=radius {
+                positions.push(Self::new(self.x + x, self.y + y));
+            }
+        }
+        positions
+    }
+
     pub fn neighbors(self, obstacles: &HashSet<Self>) -> Vec<Position> {
         let mut neighbors = Vec::with_capacity(4);
         for (x_offset, y_offset) in &[
@@ -81,6 +97,19 @@ impl Position {
     }
 }
 
+/// For entities that take up more than one tile, centered on their Position
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct Footprint {
+    pub radius: i16,
+}
+
+impl Footprint {
+    pub fn new(radius: i16) -> Self {
+        Self { radius }
+    }
+}
+
 #[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
 #[storage(BTreeStorage)]
 pub struct Sprite {
@@ -109,15 +138,10 @@ pub struct Attackable {
     pub crystals_dropped_on_death: u32,
     pub item_dropped_on_death: Option<fn(Option<Position>, &mut World) -> Entity>,
 
-    pub is_oozing: bool,
-    pub explode_on_death: (u32, u32),  // Damage, Radius
-    pub lower_spawn_times: (f32, u32), // Health percent threshold, Turns to lower by
-    pub is_magic_immune: bool,
-
-    pub oozed_stacks: u32,
-    pub cant_attack_turns: u32,
-    pub cant_move_turns: u32,
-    pub blight_stacks: u32,
+    pub accuracy: u32, // Percent chance to hit, before the target's evasion is taken off
+    pub evasion: u32,
+    pub resistances: HashMap<DamageType, Resistance>,
+    pub is_invulnerable: bool,
 }
 
 impl Attackable {
@@ -135,17 +159,290 @@ impl Attackable {
             crystals_dropped_on_death,
             item_dropped_on_death,
 
-            is_oozing: false,
-            explode_on_death: (0, 0),
-            lower_spawn_times: (0.0, 0),
-            is_magic_immune: false,
+            accuracy: 100,
+            evasion: 0,
+            resistances: HashMap::new(),
+            is_invulnerable: false,
+        }
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+pub enum DamageType {
+    Physical,
+    Magic,
+    Fire,
+    Blight,
+    Lightning,
+    Explosion,
+}
+
+impl DamageType {
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageType::Physical => "Physical",
+            DamageType::Magic => "Magic",
+            DamageType::Fire => "Fire",
+            DamageType::Blight => "Blight",
+            DamageType::Lightning => "Lightning",
+            DamageType::Explosion => "Explosion",
+        }
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub enum Resistance {
+    Immune,    // Takes no damage
+    Resistant, // Takes half damage, rounded up
+    Weak,      // Takes half again as much damage, rounded up
+}
+
+impl Resistance {
+    pub fn scale(self, damage: u32) -> u32 {
+        match self {
+            Resistance::Immune => 0,
+            Resistance::Resistant => damage.div_ceil(2),
+            Resistance::Weak => (damage * 3).div_ceil(2),
+        }
+    }
+
+    /// Tacked onto the end of the message for a hit
+    pub fn describe(self) -> &'static str {
+        match self {
+            Resistance::Immune => " (immune)",
+            Resistance::Resistant => " (resisted)",
+            Resistance::Weak => " (weak)",
+        }
+    }
+}
+
+/// Reactions to being in a fight, run by `attack::damage`
+#[derive(Component, Clone, Default)]
+#[storage(BTreeStorage)]
+pub struct Triggers {
+    triggers: Vec<Trigger>,
+}
+
+impl Triggers {
+    pub fn new(triggers: Vec<Trigger>) -> Self {
+        Self { triggers }
+    }
+
+    pub fn for_event(&self, event: TriggerEvent) -> Vec<Trigger> {
+        self.triggers
+            .iter()
+            .filter(|trigger| trigger.event == event)
+            .copied()
+            .collect()
+    }
+
+    pub fn descriptions(&self) -> Vec<&'static str> {
+        self.triggers
+            .iter()
+            .filter_map(|trigger| trigger.description)
+            .collect()
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct Trigger {
+    pub event: TriggerEvent,
+    pub run: fn(&mut Hit, &mut World),
+    pub description: Option<&'static str>, // Shown when examining whatever has the trigger
+}
+
+impl Trigger {
+    pub fn new(event: TriggerEvent, run: fn(&mut Hit, &mut World)) -> Self {
+        Self {
+            event,
+            run,
+            description: None,
+        }
+    }
+
+    pub fn with_description(mut self, description: &'static str) -> Self {
+        self.description = Some(description);
+        self
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub enum TriggerEvent {
+    Attack,  // Run for the attacker before the damage is dealt, and can change it
+    Struck,  // Run for the target before the damage is dealt, and can change it
+    Hit,     // Run for the attacker after the damage is dealt
+    Damaged, // Run for the target after the damage is dealt
+    Kill,    // Run for the attacker when the target dies
+    Death,   // Run for the target when it dies, before it's removed
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub struct Hit {
+    pub attacker: Option<Entity>,
+    pub target: Entity,
+    pub damage: u32,
+    pub is_melee: bool,
+    pub damage_type: DamageType,
+}
+
+/// Every status effect on an attackable, added the first time one is applied
+#[derive(Component, Debug, Clone, Default)]
+#[storage(BTreeStorage)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// Combines the effect with any effect of the same kind that's already there
+    pub fn apply(&mut self, effect: StatusEffect, stacking: Stacking) {
+        match self
+            .effects
+            .iter_mut()
+            .find(|existing_effect| existing_effect.kind == effect.kind)
+        {
+            Some(existing_effect) => {
+                let combine = |existing: u32, new: u32| match stacking {
+                    Stacking::Add => existing + new,
+                    Stacking::Refresh => existing.max(new),
+                };
+                existing_effect.stacks = combine(existing_effect.stacks, effect.stacks);
+                existing_effect.turns_left = match (existing_effect.turns_left, effect.turns_left) {
+                    (Some(existing_turns), Some(new_turns)) => {
+                        Some(combine(existing_turns, new_turns))
+                    }
+                    _ => None,
+                };
+                existing_effect.source = effect.source.or(existing_effect.source);
+            }
+            None => self.effects.push(effect),
+        }
+    }
+
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.effects.iter().find(|effect| effect.kind == kind)
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    pub fn stacks(&self, kind: StatusKind) -> u32 {
+        self.get(kind).map_or(0, |effect| effect.stacks)
+    }
+
+    /// Every effect, sorted by kind
+    pub fn effects(&self) -> Vec<StatusEffect> {
+        let mut effects = self.effects.clone();
+        effects.sort_by_key(|effect| effect.kind);
+        effects
+    }
+
+    /// Removes every effect with the tag
+    pub fn cleanse(&mut self, tag: CleanseTag) {
+        self.effects
+            .retain(|effect| !effect.kind.cleanse_tags().contains(&tag));
+    }
+
+    /// Counts down every effect that wears off, removing the ones that run out,
+    /// and returns the effects that were active this turn
+    pub fn tick(&mut self) -> Vec<StatusEffect> {
+        let active_effects = self.effects();
+        for effect in &mut self.effects {
+            if let Some(turns_left) = &mut effect.turns_left {
+                *turns_left = turns_left.saturating_sub(1);
+            }
+        }
+        self.effects.retain(|effect| effect.turns_left != Some(0));
+        active_effects
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub stacks: u32,
+    pub turns_left: Option<u32>, // None if it lasts until it's cleansed
+    pub source: Option<Entity>,
+}
 
-            oozed_stacks: 0,
-            cant_attack_turns: 0,
-            cant_move_turns: 0,
-            blight_stacks: 0,
+impl StatusEffect {
+    pub fn new(kind: StatusKind, stacks: u32, turns_left: Option<u32>) -> Self {
+        Self {
+            kind,
+            stacks,
+            turns_left,
+            source: None,
         }
     }
+
+    pub fn with_source(mut self, source: Entity) -> Self {
+        self.source = Some(source);
+        self
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+pub enum StatusKind {
+    Oozed,      // Takes extra damage from oozing attackers for each stack
+    Blight,     // Takes damage every turn
+    Burning,    // Takes damage every turn for each stack
+    CantMove,   // Can't move until it wears off
+    CantAttack, // Can't attack until it wears off
+    Dazzled,    // Can only see a few tiles away until it wears off
+}
+
+impl StatusKind {
+    pub fn cleanse_tags(self) -> &'static [CleanseTag] {
+        match self {
+            StatusKind::Oozed => &[CleanseTag::Debuff],
+            StatusKind::Blight => &[CleanseTag::Debuff, CleanseTag::Poison],
+            StatusKind::Burning => &[CleanseTag::Debuff, CleanseTag::Fire],
+            StatusKind::CantMove | StatusKind::CantAttack => {
+                &[CleanseTag::Debuff, CleanseTag::Control]
+            }
+            StatusKind::Dazzled => &[CleanseTag::Debuff],
+        }
+    }
+
+    pub fn describe(self, effect: &StatusEffect) -> String {
+        let turns_left = effect.turns_left.unwrap_or(0);
+        match self {
+            StatusKind::Oozed => format!("Oozed x{}", effect.stacks),
+            StatusKind::Blight => format!("Blighted x{}", turns_left),
+            StatusKind::Burning => format!("Burning for {} turns", turns_left),
+            StatusKind::CantMove => format!("Can't move for {} turns", turns_left),
+            StatusKind::CantAttack => format!("Can't attack for {} turns", turns_left),
+            StatusKind::Dazzled => format!("Dazzled for {} turns", turns_left),
+        }
+    }
+
+    /// The color of the pip drawn over anything with the effect
+    pub fn pip_color(self) -> (u8, u8, u8) {
+        match self {
+            StatusKind::Oozed => (170, 190, 40),
+            StatusKind::Blight => (30, 140, 30),
+            StatusKind::Burning => (240, 120, 30),
+            StatusKind::CantMove => (140, 90, 40),
+            StatusKind::CantAttack => (160, 160, 160),
+            StatusKind::Dazzled => (240, 230, 150),
+        }
+    }
+}
+
+/// How a status effect combines with one of the same kind that's already there
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub enum Stacking {
+    Add,     // Adds the stacks and turns together
+    Refresh, // Keeps whichever has more, for effects reapplied every turn like standing in gas
+}
+
+/// What can remove a status effect early
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub enum CleanseTag {
+    Debuff,  // Every harmful effect, removed on taking the staircase
+    Poison,  // Blight
+    Fire,    // Put out by water
+    Control, // Stops the entity from acting
 }
 
 #[derive(Component, Copy, Clone)]
@@ -160,6 +457,13 @@ impl AI {
     }
 }
 
+#[derive(Component, Debug, Hash, PartialEq, Eq, Clone)]
+#[storage(BTreeStorage)]
+pub struct CachedPath {
+    pub goal: Position,
+    pub path: Vec<Position>,
+}
+
 #[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
 #[storage(BTreeStorage)]
 pub struct Counter(pub u32);
@@ -176,6 +480,7 @@ pub struct Player {
     pub inventory: [Option<Entity>; 16],
     pub turns_taken: u32,
     pub heal_turns_left: u32,
+    pub selected_target: Option<Entity>,
 }
 
 impl Player {
@@ -188,6 +493,7 @@ impl Player {
             inventory,
             turns_taken: 0,
             heal_turns_left: 10,
+            selected_target: None,
         }
     }
 }
@@ -200,6 +506,17 @@ pub enum ItemSlot {
     Four,
 }
 
+impl ItemSlot {
+    pub fn inventory_index(self) -> usize {
+        match self {
+            ItemSlot::One => 0,
+            ItemSlot::Two => 1,
+            ItemSlot::Three => 2,
+            ItemSlot::Four => 3,
+        }
+    }
+}
+
 #[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
 #[storage(BTreeStorage)]
 pub struct Staircase {}
@@ -222,6 +539,11 @@ impl Spawner {
         }
     }
 
+    /// Makes the spawner spawn on its next tick
+    pub fn trigger(&mut self) {
+        self.turns_since_last_spawn = self.turns_per_spawn;
+    }
+
     pub fn tick(&mut self) -> bool {
         self.turns_since_last_spawn += 1;
         if self.turns_since_last_spawn >= self.turns_per_spawn {
@@ -233,22 +555,231 @@ impl Spawner {
     }
 }
 
+/// A warning shown on a tile before it's hit by an area attack
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct Telegraph {
+    pub turns_left: u32,
+    pub damage: u32,
+    pub damage_type: DamageType,
+    pub source: Entity,
+}
+
+impl Telegraph {
+    pub fn new(turns_left: u32, damage: u32, damage_type: DamageType, source: Entity) -> Self {
+        Self {
+            turns_left,
+            damage,
+            damage_type,
+            source,
+        }
+    }
+}
+
+/// Deletes the entity once its turns run out
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct Temporary {
+    pub turns_left: u32,
+}
+
+impl Temporary {
+    pub fn new(turns_left: u32) -> Self {
+        Self { turns_left }
+    }
+}
+
+/// A wall that crumbles into rubble once it's taken enough damage
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct Breakable {
+    pub durability: u32,
+    pub max_durability: u32,
+}
+
+impl Breakable {
+    pub fn new(max_durability: u32) -> Self {
+        Self {
+            durability: max_durability,
+            max_durability,
+        }
+    }
+}
+
+/// Blocks movement and line of sight while closed, locked doors need a Key to open
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct Door {
+    pub is_open: bool,
+    pub is_locked: bool,
+}
+
+/// Looks like a wall until it's found by searching or bumping into it
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct SecretWall {}
+
+/// For enemies that open closed, unlocked doors by walking into them
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct OpensDoors {}
+
+/// For oozes, which don't pick up ooze stacks from standing in stench
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct IgnoresStench {}
+
+/// Set off by anything that moves onto it, hidden until it's detected
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct Trap {
+    pub kind: TrapKind,
+    pub is_hidden: bool,
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub enum TrapKind {
+    Spike,    // Damages whatever steps on it
+    Snare,    // Stops whatever steps on it from moving for a few turns
+    Alarm,    // Makes every spawner spawn, then breaks
+    Teleport, // Sends whatever steps on it somewhere random on the floor
+    SleepGas, // Releases a cloud of sleep gas
+}
+
+/// A floor tile with an effect on whatever moves onto or stands on it
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct Terrain {
+    pub kind: TerrainKind,
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub enum TerrainKind {
+    ShallowWater, // Slows down whatever wades into it
+    Chasm,        // Can't be walked over, but can be attacked across
+    ToxicMoss,    // Blights whatever stands on it
+    Rubble,       // Slow to climb over
+}
+
+impl TerrainKind {
+    /// How many moves it takes to enter the tile, None if it can't be walked onto
+    pub fn movement_cost(self) -> Option<u32> {
+        match self {
+            TerrainKind::ShallowWater => Some(2),
+            TerrainKind::Chasm => None,
+            TerrainKind::ToxicMoss => Some(1),
+            TerrainKind::Rubble => Some(3),
+        }
+    }
+}
+
+/// Stops the entity from moving until it's made its way through the slow terrain it entered
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct MovementDelay {
+    pub turns_left: u32, // Counts down once a round, after the enemies' turn
+}
+
+/// Burns whatever stands in it and spreads to flammable tiles until it burns out
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct Fire {
+    pub turns_left: u32,
+}
+
+/// One tile of a gas cloud, which spreads out to the open tiles around it and thins out over time
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct Gas {
+    pub kind: GasKind,
+    pub concentration: u32,
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub enum GasKind {
+    Stench, // Oozes whatever breathes it in
+    Poison, // Blights whatever breathes it in
+    Sleep,  // Stops whatever breathes it in from moving or attacking
+}
+
+/// For items that unlock a locked door, used up when they do
+#[derive(Component, Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[storage(BTreeStorage)]
+pub struct Key {}
+
 #[derive(Component, Copy, Clone)]
 #[storage(BTreeStorage)]
 pub struct Item {
     pub crystals_per_use: u32,
+    pub description: &'static str,
+    pub stats: ItemStats,
     pub try_use: fn(Entity, &mut World) -> ItemResult,
 }
 
 impl Item {
-    pub fn new(crystals_per_use: u32, try_use: fn(Entity, &mut World) -> ItemResult) -> Self {
+    pub fn new(
+        crystals_per_use: u32,
+        description: &'static str,
+        stats: ItemStats,
+        try_use: fn(Entity, &mut World) -> ItemResult,
+    ) -> Self {
         Self {
             crystals_per_use,
+            description,
+            stats,
             try_use,
         }
     }
 }
 
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub struct ItemStats {
+    pub damage: Option<(u32, u32)>, // Minimum damage, Maximum damage
+    pub targeting: Option<Targeting>,
+    pub is_melee: bool,
+    pub damage_type: DamageType,
+    pub critical_hit: Option<(u32, u32)>, // Percent chance, Damage multiplier
+    pub special_effects: &'static [&'static str],
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub struct Targeting {
+    pub minimum_range: u32,
+    pub maximum_range: u32,
+    pub allows_diagonal: bool,
+    pub line_of_sight: LineOfSight,
+}
+
+impl Targeting {
+    pub fn new(
+        minimum_range: u32,
+        maximum_range: u32,
+        allows_diagonal: bool,
+        line_of_sight: LineOfSight,
+    ) -> Self {
+        Self {
+            minimum_range,
+            maximum_range,
+            allows_diagonal,
+            line_of_sight,
+        }
+    }
+}
+
+/// Attacks are always blocked by walls
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub enum LineOfSight {
+    Piercing,    // Passes through entities
+    FirstEntity, // Stops at the first entity in the way
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub enum AttackResult {
+    Missed,
+    Hit,
+    Killed,
+}
+
 #[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
 pub struct ItemResult {
     pub should_end_turn: bool,
@@ -259,6 +790,8 @@ pub struct ItemResult {
 pub enum GameState {
     NewGame,
     BagUI(BagUIState),
+    Examine(i32, i32),          // Cursor position in the viewport (x, y)
+    Targeting(ItemSlot, usize), // Item being used, Selected target index
     PlayerTurn,
     EnemyTurn,
 }
@@ -350,6 +883,10 @@ pub struct ScrollInfo {
     pub scroll_of_entanglement_identified: bool,
     pub scroll_of_lightning_sprite: &'static str,
     pub scroll_of_lightning_identified: bool,
+    pub scroll_of_fire_sprite: &'static str,
+    pub scroll_of_fire_identified: bool,
+    pub scroll_of_miasma_sprite: &'static str,
+    pub scroll_of_miasma_identified: bool,
 }
 
 impl ScrollInfo {
@@ -374,6 +911,10 @@ impl ScrollInfo {
             scroll_of_entanglement_identified: false,
             scroll_of_lightning_sprite: colors.pop().unwrap(),
             scroll_of_lightning_identified: false,
+            scroll_of_fire_sprite: colors.pop().unwrap(),
+            scroll_of_fire_identified: false,
+            scroll_of_miasma_sprite: colors.pop().unwrap(),
+            scroll_of_miasma_identified: false,
         }
     }
 }
@@ -387,6 +928,13 @@ impl RNG {
     }
 }
 
+/// Spawn points of the arena on the current boss floor
+#[derive(Debug, Clone)]
+pub struct BossArena {
+    pub add_spawn_positions: Vec<Position>,
+    pub staircase_position: Position,
+}
+
 #[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
 pub enum Direction {
     Up,
@@ -399,6 +947,22 @@ pub enum Direction {
     UpRight,
 }
 
+impl Direction {
+    pub fn from_offset(x: i16, y: i16) -> Option<Self> {
+        match (x, y) {
+            (0, 1) => Some(Direction::Up),
+            (0, -1) => Some(Direction::Down),
+            (-1, 0) => Some(Direction::Left),
+            (1, 0) => Some(Direction::Right),
+            (-1, 1) => Some(Direction::UpLeft),
+            (-1, -1) => Some(Direction::DownLeft),
+            (1, -1) => Some(Direction::DownRight),
+            (1, 1) => Some(Direction::UpRight),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
 pub enum Rarity {
     Common,
@@ -0,0 +1,149 @@
+use crate::data::*;
+use specs::{Entity, Join, World, WorldExt};
+
+/// Returns the door at the position, if there is one
+pub fn door_at(position: Position, world: &World) -> Option<Entity> {
+    let entities = world.entities();
+    let position_data = world.read_storage::<Position>();
+    let door_data = world.read_storage::<Door>();
+    (&entities, &position_data, &door_data)
+        .join()
+        .find(|(_, door_position, _)| **door_position == position)
+        .map(|(entity, _, _)| entity)
+}
+
+pub fn open_door(door_entity: Entity, world: &mut World) {
+    let mut door_data = world.write_storage::<Door>();
+    let mut intangible_data = world.write_storage::<Intangible>();
+    let mut sprite_data = world.write_storage::<Sprite>();
+    let door = door_data.get_mut(door_entity).unwrap();
+    door.is_open = true;
+    door.is_locked = false;
+    intangible_data.insert(door_entity, Intangible {}).unwrap();
+    sprite_data
+        .insert(
+            door_entity,
+            Sprite {
+                id: "door_open",
+                double_sized: false,
+                in_foreground: false,
+            },
+        )
+        .unwrap();
+}
+
+/// Returns false if something is standing in the doorway
+pub fn try_close_door(door_entity: Entity, world: &mut World) -> bool {
+    let door_position = *world.read_storage::<Position>().get(door_entity).unwrap();
+    let is_blocked = {
+        let entities = world.entities();
+        let position_data = world.read_storage::<Position>();
+        let intangible_data = world.read_storage::<Intangible>();
+        let footprint_data = world.read_storage::<Footprint>();
+        (
+            &entities,
+            &position_data,
+            !&intangible_data,
+            footprint_data.maybe(),
+        )
+            .join()
+            .any(|(entity, position, _, footprint)| {
+                entity != door_entity
+                    && position
+                        .footprint_positions(footprint)
+                        .contains(&door_position)
+            })
+    };
+    if is_blocked {
+        return false;
+    }
+
+    let mut door_data = world.write_storage::<Door>();
+    let mut intangible_data = world.write_storage::<Intangible>();
+    let mut sprite_data = world.write_storage::<Sprite>();
+    door_data.get_mut(door_entity).unwrap().is_open = false;
+    intangible_data.remove(door_entity);
+    sprite_data
+        .insert(door_entity, Sprite::new("door_closed"))
+        .unwrap();
+    true
+}
+
+/// Opens, closes or unlocks the door for the player, returning whether it took their turn
+pub fn player_use_door(door_entity: Entity, world: &mut World) -> bool {
+    let door = *world.read_storage::<Door>().get(door_entity).unwrap();
+    if door.is_open {
+        if try_close_door(door_entity, world) {
+            true
+        } else {
+            world.fetch_mut::<MessageLog>().new_message(
+                "Something is in the way of the door",
+                MessageColor::White,
+                MessageDisplayLength::Short,
+            );
+            false
+        }
+    } else if door.is_locked {
+        let key_slot = {
+            let player_data = world.read_storage::<Player>();
+            let key_data = world.read_storage::<Key>();
+            let player = (&player_data).join().next().unwrap();
+            player.inventory.iter().position(|item_slot| {
+                matches!(item_slot, Some(item_entity) if key_data.contains(*item_entity))
+            })
+        };
+        match key_slot {
+            Some(key_slot) => {
+                let key_entity = {
+                    let mut player_data = world.write_storage::<Player>();
+                    let player = (&mut player_data).join().next().unwrap();
+                    player.inventory[key_slot].take().unwrap()
+                };
+                world.delete_entity(key_entity).unwrap();
+                open_door(door_entity, world);
+                world.fetch_mut::<MessageLog>().new_message(
+                    "You unlocked the door with your key",
+                    MessageColor::Green,
+                    MessageDisplayLength::Medium,
+                );
+                true
+            }
+            None => {
+                world.fetch_mut::<MessageLog>().new_message(
+                    "The door is locked, there must be a key somewhere on this floor",
+                    MessageColor::White,
+                    MessageDisplayLength::Medium,
+                );
+                false
+            }
+        }
+    } else {
+        open_door(door_entity, world);
+        true
+    }
+}
+
+/// Turns every secret wall within the radius into floor, returning how many were found
+pub fn reveal_secret_walls(center: Position, radius: u32, world: &mut World) -> u32 {
+    let secret_walls = {
+        let entities = world.entities();
+        let position_data = world.read_storage::<Position>();
+        let secret_wall_data = world.read_storage::<SecretWall>();
+        (&entities, &position_data, &secret_wall_data)
+            .join()
+            .filter(|(_, position, _)| position.chebyshev_distance_from(center) <= radius)
+            .map(|(entity, _, _)| entity)
+            .collect::<Vec<Entity>>()
+    };
+    for secret_wall in &secret_walls {
+        world.delete_entity(*secret_wall).unwrap();
+    }
+    if !secret_walls.is_empty() {
+        world.fetch_mut::<MessageLog>().new_message(
+            "You found a secret passage!",
+            MessageColor::Green,
+            MessageDisplayLength::Medium,
+        );
+    }
+    secret_walls.len() as u32
+}
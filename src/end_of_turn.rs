@@ -1,14 +1,18 @@
-use crate::attack::damage;
 use crate::data::*;
+use crate::fire::{catch_fire, fire_positions, tick_fires};
+use crate::gas::{breathe_gas, gas_positions, tick_gas};
+use crate::status_effects::tick_status_effects;
+use crate::terrain::{apply_standing_effects, terrain_positions, tick_movement_delays};
 use specs::{Entity, Join, World, WorldExt};
 
 pub fn end_of_turn(world: &mut World) {
+    let is_player_turn = *world.fetch::<GameState>() == GameState::PlayerTurn;
     let entities_to_process = {
         let entities = world.entities();
         let player_data = world.read_storage::<Player>();
         let attackable_data = world.read_storage::<Attackable>();
 
-        if *world.fetch::<GameState>() == GameState::PlayerTurn {
+        if is_player_turn {
             vec![(&entities, &player_data).join().next().unwrap().0]
         } else {
             (&entities, &attackable_data)
@@ -18,39 +22,25 @@ pub fn end_of_turn(world: &mut World) {
         }
     };
 
+    // This runs after both the player's and the enemies' turns, but fire, gas and slow terrain only tick once a round
+    if !is_player_turn {
+        tick_fires(world);
+        tick_gas(world);
+        tick_movement_delays(world);
+    }
+    let terrain = terrain_positions(world);
+    let burning_positions = fire_positions(world);
+    let gas = gas_positions(world);
     for entity in entities_to_process {
-        let mut apply_blight_damage = false;
-
-        {
-            let mut attackable_data = world.write_storage::<Attackable>();
-            let entity_attackable = attackable_data.get_mut(entity).unwrap();
-
-            if entity_attackable.cant_attack_turns > 0 {
-                entity_attackable.cant_attack_turns -= 1;
-            }
-
-            if entity_attackable.cant_move_turns > 0 {
-                entity_attackable.cant_move_turns -= 1;
-            }
-
-            if entity_attackable.blight_stacks > 0 {
-                entity_attackable.blight_stacks -= 1;
-                apply_blight_damage = true;
-            }
+        // Something that exploded earlier in the turn could have killed it
+        if !world.is_alive(entity) {
+            continue;
         }
 
-        if apply_blight_damage {
-            {
-                let mut message_log = world.fetch_mut::<MessageLog>();
-                let name_data = world.read_storage::<Name>();
-                let entity_name = name_data.get(entity).unwrap();
-                message_log.new_message(
-                    format!("{} took 1 damage from blight", entity_name.get_text()),
-                    MessageColor::Green,
-                    MessageDisplayLength::Short,
-                );
-            }
-            damage(1, false, false, None, entity, world);
-        }
+        catch_fire(entity, &burning_positions, world);
+        breathe_gas(entity, &gas, world);
+        apply_standing_effects(entity, &terrain, world);
+
+        tick_status_effects(entity, world);
     }
 }
@@ -1,4 +1,5 @@
 use crate::data::{Player, Position, AI};
+use crate::movement::DistanceMap;
 use specs::{Entity, Join, World, WorldExt};
 
 pub fn enemy_controller_system(world: &mut World) {
@@ -22,11 +23,17 @@ pub fn enemy_controller_system(world: &mut World) {
             .collect::<Vec<(AI, Position, Entity)>>()
     };
     ai_list.sort_unstable_by(|(_, p1, _), (_, p2, _)| {
-        p1.distance_from(*p2)
+        p1.distance_from(player_position)
             .cmp(&p2.distance_from(player_position))
     });
 
+    let distance_map = DistanceMap::new(player_position, world);
+    world.insert(distance_map);
+
     for (ai, _, ai_entity) in &ai_list {
-        (ai.run)(*ai_entity, world);
+        // Earlier AI can kill later ones, such as by exploding or pushing them into traps
+        if world.is_alive(*ai_entity) {
+            (ai.run)(*ai_entity, world);
+        }
     }
 }
@@ -1,11 +1,15 @@
 use crate::attack::*;
 use crate::data::*;
+use crate::entities::{create_spawner, create_wall_with_sprite};
+use crate::gas::release_gas;
 use crate::items;
 use crate::movement::*;
+use crate::status_effects::apply_status;
+use crate::telegraphs::telegraph_area;
+use crate::triggers::{explode, lower_spawn_times, ooze_triggers};
 use rand::seq::SliceRandom;
 use rand::Rng;
 use specs::{Builder, Entity, Join, World, WorldExt};
-use std::collections::HashSet;
 
 pub fn create_random_layer1(rarity: Rarity, position: Position, world: &mut World) -> Entity {
     let create_function = {
@@ -26,12 +30,13 @@ pub fn create_random_layer1(rarity: Rarity, position: Position, world: &mut Worl
 }
 
 pub fn create_phase_bat(position: Position, world: &mut World) -> Entity {
-    let attackable = Attackable::new(
+    let mut attackable = Attackable::new(
         9,
         20,
         items::create_random_layer1(Rarity::Common, world),
         false,
     );
+    attackable.evasion = 35;
     world
         .create_entity()
         .with(Name::new("Phase Bat", false))
@@ -41,19 +46,37 @@ pub fn create_phase_bat(position: Position, world: &mut World) -> Entity {
                 let entities = world.entities();
                 (&entities, &player_data).join().next().unwrap().0
             };
-            match try_attack(3, true, false, 1, 1, ai_entity, player_entity, world) {
-                Ok(true) => {}
-                Ok(false) => {
+            match try_attack(
+                3,
+                true,
+                DamageType::Physical,
+                None,
+                Targeting::new(1, 1, false, LineOfSight::FirstEntity),
+                ai_entity,
+                player_entity,
+                world,
+            ) {
+                Ok(AttackResult::Missed) | Ok(AttackResult::Killed) => {}
+                Ok(AttackResult::Hit) => {
                     let attack_twice = {
                         let rng = &mut world.fetch_mut::<RNG>().0;
                         rng.gen_ratio(1, 5)
                     };
                     if attack_twice {
-                        let _ = try_attack(2, true, false, 1, 1, ai_entity, player_entity, world);
+                        let _ = try_attack(
+                            2,
+                            true,
+                            DamageType::Physical,
+                            None,
+                            Targeting::new(1, 1, false, LineOfSight::FirstEntity),
+                            ai_entity,
+                            player_entity,
+                            world,
+                        );
                     }
                 }
                 Err(_) => {
-                    let _ = try_move_towards(ai_entity, player_entity, world);
+                    let _ = try_move_towards_player(ai_entity, world);
                 }
             }
         }))
@@ -64,13 +87,12 @@ pub fn create_phase_bat(position: Position, world: &mut World) -> Entity {
 }
 
 pub fn create_danger_spider(position: Position, world: &mut World) -> Entity {
-    let mut attackable = Attackable::new(
+    let attackable = Attackable::new(
         11,
         20,
         items::create_random_layer1(Rarity::Common, world),
         false,
     );
-    attackable.lower_spawn_times = (0.5, 3);
     world
         .create_entity()
         .with(Name::new("Danger! Spider", false))
@@ -80,13 +102,28 @@ pub fn create_danger_spider(position: Position, world: &mut World) -> Entity {
                 let entities = world.entities();
                 (&entities, &player_data).join().next().unwrap().0
             };
-            if try_attack(5, true, false, 1, 1, ai_entity, player_entity, world).is_err() {
-                let _ = try_move_towards(ai_entity, player_entity, world);
+            if try_attack(
+                5,
+                true,
+                DamageType::Physical,
+                None,
+                Targeting::new(1, 1, false, LineOfSight::FirstEntity),
+                ai_entity,
+                player_entity,
+                world,
+            )
+            .is_err()
+            {
+                let _ = try_move_towards_player(ai_entity, world);
             }
         }))
         .with(position)
         .with(attackable)
         .with(Sprite::new("danger_spider"))
+        .with(Triggers::new(vec![Trigger::new(
+            TriggerEvent::Struck,
+            |hit, world| lower_spawn_times(hit.target, 0.5, 3, world),
+        )]))
         .build()
 }
 
@@ -97,7 +134,9 @@ pub fn create_pungent_ooze(position: Position, world: &mut World) -> Entity {
         items::create_random_layer1(Rarity::Common, world),
         false,
     );
-    attackable.is_oozing = true;
+    attackable
+        .resistances
+        .insert(DamageType::Blight, Resistance::Resistant);
     world
         .create_entity()
         .with(Name::new("Pungent Ooze", false))
@@ -107,23 +146,42 @@ pub fn create_pungent_ooze(position: Position, world: &mut World) -> Entity {
                 let entities = world.entities();
                 (&entities, &player_data).join().next().unwrap().0
             };
-            if try_attack(4, true, false, 1, 1, ai_entity, player_entity, world).is_err() {
-                let _ = try_move_towards(ai_entity, player_entity, world);
+            if try_attack(
+                4,
+                true,
+                DamageType::Physical,
+                None,
+                Targeting::new(1, 1, false, LineOfSight::FirstEntity),
+                ai_entity,
+                player_entity,
+                world,
+            )
+            .is_err()
+            {
+                let previous_position = *world.read_storage::<Position>().get(ai_entity).unwrap();
+                if try_move_towards_player(ai_entity, world).is_ok() {
+                    release_gas(GasKind::Stench, previous_position, 40, world);
+                }
             }
         }))
         .with(position)
         .with(attackable)
         .with(Sprite::new("pungent_ooze"))
+        .with(Triggers::new(ooze_triggers()))
+        .with(IgnoresStench {})
         .build()
 }
 
 pub fn create_skeleton_scout(position: Position, world: &mut World) -> Entity {
-    let attackable = Attackable::new(
+    let mut attackable = Attackable::new(
         12,
         30,
         items::create_random_layer1(Rarity::Uncommon, world),
         false,
     );
+    attackable
+        .resistances
+        .insert(DamageType::Blight, Resistance::Resistant);
     world
         .create_entity()
         .with(Name::new("Skeleton Scout", false))
@@ -142,7 +200,12 @@ pub fn create_skeleton_scout(position: Position, world: &mut World) -> Entity {
                 (*ai_position, player_entity, *player_position)
             };
 
-            if can_attack(1, 2, ai_entity, player_entity, world) {
+            if can_attack(
+                Targeting::new(1, 2, false, LineOfSight::FirstEntity),
+                ai_entity,
+                player_entity,
+                world,
+            ) {
                 let change_in_x = ai_position.x - player_position.x;
                 let change_in_y = ai_position.y - player_position.y;
                 let move_before_attacking = {
@@ -162,7 +225,16 @@ pub fn create_skeleton_scout(position: Position, world: &mut World) -> Entity {
                     }
                     let _ = try_move(ai_entity, direction_to_move, world);
                 }
-                let _ = try_attack(4, false, false, 1, 2, ai_entity, player_entity, world);
+                let _ = try_attack(
+                    4,
+                    false,
+                    DamageType::Physical,
+                    None,
+                    Targeting::new(1, 2, false, LineOfSight::FirstEntity),
+                    ai_entity,
+                    player_entity,
+                    world,
+                );
             } else {
                 let _ = try_move_towards(ai_entity, player_entity, world);
             }
@@ -170,6 +242,7 @@ pub fn create_skeleton_scout(position: Position, world: &mut World) -> Entity {
         .with(position)
         .with(attackable)
         .with(Sprite::new("skeleton_scout"))
+        .with(OpensDoors {})
         .build()
 }
 
@@ -180,7 +253,9 @@ pub fn create_volatile_husk(position: Position, world: &mut World) -> Entity {
         items::create_random_layer1(Rarity::Uncommon, world),
         false,
     );
-    attackable.explode_on_death = (6, 1);
+    attackable
+        .resistances
+        .insert(DamageType::Fire, Resistance::Weak);
     world
         .create_entity()
         .with(Name::new("Volatile Husk", false))
@@ -190,20 +265,40 @@ pub fn create_volatile_husk(position: Position, world: &mut World) -> Entity {
                 let entities = world.entities();
                 (&entities, &player_data).join().next().unwrap().0
             };
-            if try_attack(3, true, false, 1, 1, ai_entity, player_entity, world).is_err() {
-                let _ = try_move_towards(ai_entity, player_entity, world);
+            if try_attack(
+                3,
+                true,
+                DamageType::Physical,
+                None,
+                Targeting::new(1, 1, false, LineOfSight::FirstEntity),
+                ai_entity,
+                player_entity,
+                world,
+            )
+            .is_err()
+            {
+                let _ = try_move_towards_player(ai_entity, world);
             }
         }))
         .with(position)
         .with(attackable)
         .with(Sprite::new("volatile_husk"))
+        .with(Triggers::new(vec![Trigger::new(
+            TriggerEvent::Death,
+            |hit, world| explode(hit.target, 6, 1, world),
+        )
+        .with_description("Explodes on death for 6 damage")]))
         .build()
 }
 
 pub fn create_arcane_ooze(position: Position, world: &mut World) -> Entity {
     let mut attackable = Attackable::new(18, 50, Some(items::create_random_good_scroll), false);
-    attackable.is_oozing = true;
-    attackable.is_magic_immune = true;
+    attackable
+        .resistances
+        .insert(DamageType::Magic, Resistance::Immune);
+    attackable
+        .resistances
+        .insert(DamageType::Lightning, Resistance::Immune);
     world
         .create_entity()
         .with(Name::new("Arcane Ooze", false))
@@ -213,23 +308,39 @@ pub fn create_arcane_ooze(position: Position, world: &mut World) -> Entity {
                 let entities = world.entities();
                 (&entities, &player_data).join().next().unwrap().0
             };
-            if try_attack(6, true, true, 1, 1, ai_entity, player_entity, world).is_err() {
-                let _ = try_move_towards(ai_entity, player_entity, world);
+            if try_attack(
+                6,
+                true,
+                DamageType::Magic,
+                None,
+                Targeting::new(1, 1, false, LineOfSight::FirstEntity),
+                ai_entity,
+                player_entity,
+                world,
+            )
+            .is_err()
+            {
+                let _ = try_move_towards_player(ai_entity, world);
             }
         }))
         .with(position)
         .with(attackable)
         .with(Sprite::new("arcane_ooze"))
+        .with(Triggers::new(ooze_triggers()))
+        .with(IgnoresStench {})
         .build()
 }
 
 pub fn create_soul_spectre(position: Position, world: &mut World) -> Entity {
-    let attackable = Attackable::new(
+    let mut attackable = Attackable::new(
         16,
         50,
         items::create_random_layer1(Rarity::Rare, world),
         false,
     );
+    attackable
+        .resistances
+        .insert(DamageType::Magic, Resistance::Weak);
     world
         .create_entity()
         .with(Name::new("Soul Spectre", false))
@@ -277,14 +388,7 @@ pub fn create_soul_spectre(position: Position, world: &mut World) -> Entity {
                         let position_data = world.read_storage::<Position>();
                         *position_data.get(ai_entity).unwrap()
                     };
-                    let obstacles = {
-                        let position_data = world.read_storage::<Position>();
-                        let intangible_data = world.read_storage::<Intangible>();
-                        (&position_data, !&intangible_data)
-                            .join()
-                            .map(|(position, _)| *position)
-                            .collect::<HashSet<Position>>()
-                    };
+                    let obstacles = obstacle_positions(world);
                     for direction in &[
                         Direction::Up,
                         Direction::Down,
@@ -303,9 +407,20 @@ pub fn create_soul_spectre(position: Position, world: &mut World) -> Entity {
                         let entities = world.entities();
                         (&entities, &player_data).join().next().unwrap().0
                     };
-                    if try_attack(5, true, false, 1, 1, ai_entity, player_entity, world).is_err() {
-                        let _ = try_move_towards(ai_entity, player_entity, world);
-                        let _ = try_move_towards(ai_entity, player_entity, world);
+                    if try_attack(
+                        5,
+                        true,
+                        DamageType::Physical,
+                        None,
+                        Targeting::new(1, 1, false, LineOfSight::FirstEntity),
+                        ai_entity,
+                        player_entity,
+                        world,
+                    )
+                    .is_err()
+                    {
+                        let _ = try_move_towards_player(ai_entity, world);
+                        let _ = try_move_towards_player(ai_entity, world);
                     }
                 }
             }
@@ -327,8 +442,19 @@ pub fn create_discordant_soul(position: Position, world: &mut World) -> Entity {
                 let entities = world.entities();
                 (&entities, &player_data).join().next().unwrap().0
             };
-            if try_attack(3, true, false, 1, 1, ai_entity, player_entity, world).is_err() {
-                let _ = try_move_towards(ai_entity, player_entity, world);
+            if try_attack(
+                3,
+                true,
+                DamageType::Physical,
+                None,
+                Targeting::new(1, 1, false, LineOfSight::FirstEntity),
+                ai_entity,
+                player_entity,
+                world,
+            )
+            .is_err()
+            {
+                let _ = try_move_towards_player(ai_entity, world);
             }
         }))
         .with(position)
@@ -337,46 +463,460 @@ pub fn create_discordant_soul(position: Position, world: &mut World) -> Entity {
         .build()
 }
 
+pub fn create_lesser_demon(position: Position, world: &mut World) -> Entity {
+    let mut attackable = Attackable::new(8, 5, None, false);
+    attackable
+        .resistances
+        .insert(DamageType::Fire, Resistance::Resistant);
+    world
+        .create_entity()
+        .with(Name::new("Lesser Demon", false))
+        .with(AI::new(|ai_entity, world| {
+            let player_entity = {
+                let player_data = world.read_storage::<Player>();
+                let entities = world.entities();
+                (&entities, &player_data).join().next().unwrap().0
+            };
+            if try_attack(
+                4,
+                true,
+                DamageType::Physical,
+                None,
+                Targeting::new(1, 1, false, LineOfSight::FirstEntity),
+                ai_entity,
+                player_entity,
+                world,
+            )
+            .is_err()
+            {
+                let _ = try_move_towards_player(ai_entity, world);
+            }
+        }))
+        .with(position)
+        .with(attackable)
+        .with(Sprite::new("lesser_demon"))
+        .with(OpensDoors {})
+        .build()
+}
+
 pub fn create_siro_king_of_hell(position: Position, world: &mut World) -> Entity {
-    let attackable = Attackable::new(
+    let mut attackable = Attackable::new(
         50,
         200,
         items::create_random_layer1(Rarity::Epic, world),
         true,
     );
+    attackable
+        .resistances
+        .insert(DamageType::Fire, Resistance::Resistant);
     world
         .create_entity()
         .with(Name::new("Siro, King of Hell", false))
         .with(AI::new(|ai_entity, world| {
-            // TODO
+            // Counter(0) = Dormant, Counter(1) = Phase 1, Counter(2) = Summoned demons, Counter(3) = Enraged
+            let (ai_name, ai_position, ai_health_percentage, player_entity, player_position) = {
+                let name_data = world.read_storage::<Name>();
+                let position_data = world.read_storage::<Position>();
+                let attackable_data = world.read_storage::<Attackable>();
+                let player_data = world.read_storage::<Player>();
+                let entities = world.entities();
+                let ai_attackable = attackable_data.get(ai_entity).unwrap();
+                let (player_entity, _, player_position) = (&entities, &player_data, &position_data)
+                    .join()
+                    .next()
+                    .unwrap();
+                (
+                    *name_data.get(ai_entity).unwrap(),
+                    *position_data.get(ai_entity).unwrap(),
+                    ai_attackable.current_health as f64 / ai_attackable.max_health as f64,
+                    player_entity,
+                    *player_position,
+                )
+            };
+
+            let phase = {
+                let counter_data = world.read_storage::<Counter>();
+                counter_data.get(ai_entity).unwrap().0
+            };
+            let mut new_phase = phase;
+            if new_phase == 0
+                && (ai_position.chebyshev_distance_from(player_position) <= 8
+                    || ai_health_percentage < 1.0)
+            {
+                new_phase = 1;
+            }
+            if new_phase == 1 && ai_health_percentage <= 0.6 {
+                new_phase = 2;
+            }
+            if new_phase != 0 && new_phase < 3 && ai_health_percentage < 0.3 {
+                new_phase = 3;
+            }
+            if new_phase != phase {
+                let (message, color, demons_to_summon) = match new_phase {
+                    1 => (
+                        "Another soul wanders into my domain. How delightful.",
+                        MessageColor::White,
+                        0,
+                    ),
+                    2 => (
+                        "Rise, my servants! Tear them apart!",
+                        MessageColor::Orange,
+                        2,
+                    ),
+                    _ => (
+                        "ENOUGH! I WILL BURN THIS DUNGEON TO ASH!",
+                        MessageColor::Red,
+                        3,
+                    ),
+                };
+                world.fetch_mut::<MessageLog>().new_message(
+                    format!("{}: {}", ai_name.get_text(), message),
+                    color,
+                    MessageDisplayLength::Long,
+                );
+                for summon_position in add_spawn_positions(ai_position, world)
+                    .into_iter()
+                    .take(demons_to_summon)
+                {
+                    create_lesser_demon(summon_position, world);
+                }
+                let mut counter_data = world.write_storage::<Counter>();
+                *counter_data.get_mut(ai_entity).unwrap() = Counter(new_phase);
+            }
+            if new_phase == 0 {
+                return;
+            }
+
+            let is_enraged = new_phase == 3;
+            let cast_hellfire = {
+                let rng = &mut world.fetch_mut::<RNG>().0;
+                rng.gen_ratio(1, if is_enraged { 2 } else { 4 })
+            };
+            if cast_hellfire {
+                let mut fire_positions =
+                    player_position.footprint_positions(Some(&Footprint::new(1)));
+                if is_enraged {
+                    for distance in 2..=4 {
+                        fire_positions.push(Position::new(
+                            player_position.x + distance,
+                            player_position.y,
+                        ));
+                        fire_positions.push(Position::new(
+                            player_position.x - distance,
+                            player_position.y,
+                        ));
+                        fire_positions.push(Position::new(
+                            player_position.x,
+                            player_position.y + distance,
+                        ));
+                        fire_positions.push(Position::new(
+                            player_position.x,
+                            player_position.y - distance,
+                        ));
+                    }
+                }
+                world.fetch_mut::<MessageLog>().new_message(
+                    format!(
+                        "{} raises his hands. The ground begins to glow...",
+                        ai_name.get_text()
+                    ),
+                    MessageColor::Orange,
+                    MessageDisplayLength::Medium,
+                );
+                telegraph_area(
+                    &fire_positions,
+                    1,
+                    if is_enraged { 8 } else { 6 },
+                    DamageType::Fire,
+                    ai_entity,
+                    world,
+                );
+            } else if try_attack(
+                if is_enraged { 10 } else { 7 },
+                true,
+                DamageType::Physical,
+                None,
+                Targeting::new(1, 1, false, LineOfSight::FirstEntity),
+                ai_entity,
+                player_entity,
+                world,
+            )
+            .is_err()
+            {
+                let _ = try_move_towards(ai_entity, player_entity, world);
+            }
         }))
+        .with(Counter(0))
         .with(position)
+        .with(Footprint::new(1))
         .with(attackable)
         .with(Sprite {
-            id: "placeholder",
+            id: "siro_king_of_hell",
             double_sized: true,
             in_foreground: true,
         })
         .build()
 }
 
+pub fn create_moth_swarm(position: Position, world: &mut World) -> Entity {
+    let mut attackable = Attackable::new(4, 3, None, false);
+    attackable.evasion = 20;
+    attackable
+        .resistances
+        .insert(DamageType::Fire, Resistance::Weak);
+    world
+        .create_entity()
+        .with(Name::new("Moth Swarm", false))
+        .with(AI::new(|ai_entity, world| {
+            let player_entity = {
+                let player_data = world.read_storage::<Player>();
+                let entities = world.entities();
+                (&entities, &player_data).join().next().unwrap().0
+            };
+            if try_attack(
+                2,
+                true,
+                DamageType::Physical,
+                None,
+                Targeting::new(1, 1, true, LineOfSight::FirstEntity),
+                ai_entity,
+                player_entity,
+                world,
+            )
+            .is_err()
+            {
+                let _ = try_move_towards_player(ai_entity, world);
+                let _ = try_move_towards_player(ai_entity, world);
+            }
+        }))
+        .with(position)
+        .with(attackable)
+        .with(Sprite::new("moth_swarm"))
+        .build()
+}
+
 pub fn create_xilphene_the_moth_priestess(position: Position, world: &mut World) -> Entity {
-    let attackable = Attackable::new(
-        40,
-        200,
-        items::create_random_layer1(Rarity::Epic, world),
-        true,
-    );
+    let attackable = Attackable::new(40, 200, Some(items::create_mothlight_lantern), true);
     world
         .create_entity()
         .with(Name::new("Xilphene, The Moth Priestess", false))
         .with(AI::new(|ai_entity, world| {
-            // TODO
+            // Counter(0) = Dormant, Counter(1) = Phase 1, Counter(2) = Summons moths, Counter(3) = Casts blinding flashes
+            let (ai_name, ai_position, ai_health_percentage, player_entity, player_position) = {
+                let name_data = world.read_storage::<Name>();
+                let position_data = world.read_storage::<Position>();
+                let attackable_data = world.read_storage::<Attackable>();
+                let player_data = world.read_storage::<Player>();
+                let entities = world.entities();
+                let ai_attackable = attackable_data.get(ai_entity).unwrap();
+                let (player_entity, _, player_position) = (&entities, &player_data, &position_data)
+                    .join()
+                    .next()
+                    .unwrap();
+                (
+                    *name_data.get(ai_entity).unwrap(),
+                    *position_data.get(ai_entity).unwrap(),
+                    ai_attackable.current_health as f64 / ai_attackable.max_health as f64,
+                    player_entity,
+                    *player_position,
+                )
+            };
+
+            let phase = {
+                let counter_data = world.read_storage::<Counter>();
+                counter_data.get(ai_entity).unwrap().0
+            };
+            let mut new_phase = phase;
+            if new_phase == 0
+                && (ai_position.chebyshev_distance_from(player_position) <= 8
+                    || ai_health_percentage < 1.0)
+            {
+                new_phase = 1;
+            }
+            if new_phase == 1 && ai_health_percentage <= 0.6 {
+                new_phase = 2;
+            }
+            if new_phase != 0 && new_phase < 3 && ai_health_percentage <= 0.3 {
+                new_phase = 3;
+            }
+            if new_phase != phase {
+                let (message, color) = match new_phase {
+                    1 => (
+                        "Come closer, little moth. Come towards the light.",
+                        MessageColor::White,
+                    ),
+                    2 => ("My children, the light calls to you!", MessageColor::Orange),
+                    _ => ("Behold the light, and be blinded by it!", MessageColor::Red),
+                };
+                world.fetch_mut::<MessageLog>().new_message(
+                    format!("{}: {}", ai_name.get_text(), message),
+                    color,
+                    MessageDisplayLength::Long,
+                );
+                let mut counter_data = world.write_storage::<Counter>();
+                *counter_data.get_mut(ai_entity).unwrap() = Counter(new_phase);
+            }
+            if new_phase == 0 {
+                return;
+            }
+
+            // Teleport away when the player is adjacent
+            if ai_position.chebyshev_distance_from(player_position) <= 2 {
+                let teleport_position = {
+                    let ai_footprint = Footprint::new(1);
+                    let own_positions = ai_position.footprint_positions(Some(&ai_footprint));
+                    let obstacles = obstacle_positions(world);
+                    let floor = floor_positions(world);
+                    let choices = floor
+                        .iter()
+                        .filter(|position| {
+                            (4..=6).contains(&position.chebyshev_distance_from(player_position))
+                                && position
+                                    .footprint_positions(Some(&ai_footprint))
+                                    .iter()
+                                    .all(|position| {
+                                        floor.contains(position)
+                                            && (!obstacles.contains(position)
+                                                || own_positions.contains(position))
+                                    })
+                        })
+                        .copied()
+                        .collect::<Vec<Position>>();
+                    let rng = &mut world.fetch_mut::<RNG>().0;
+                    choices.choose(rng).copied()
+                };
+                if let Some(teleport_position) = teleport_position {
+                    world.fetch_mut::<MessageLog>().new_message(
+                        format!("{} vanishes in a flash of light", ai_name.get_text()),
+                        MessageColor::White,
+                        MessageDisplayLength::Short,
+                    );
+                    let mut position_data = world.write_storage::<Position>();
+                    *position_data.get_mut(ai_entity).unwrap() = teleport_position;
+                    world.write_storage::<CachedPath>().remove(ai_entity);
+                    return;
+                }
+            }
+
+            let (summon_moths, cast_debuff) = {
+                let rng = &mut world.fetch_mut::<RNG>().0;
+                (
+                    new_phase >= 2 && rng.gen_ratio(1, 5),
+                    rng.gen_ratio(1, if new_phase == 3 { 4 } else { 6 }),
+                )
+            };
+            let moth_swarms_alive = {
+                let name_data = world.read_storage::<Name>();
+                (&name_data)
+                    .join()
+                    .filter(|name| name.text == "Moth Swarm")
+                    .count()
+            };
+            if summon_moths && moth_swarms_alive < 6 {
+                world.fetch_mut::<MessageLog>().new_message(
+                    format!(
+                        "A cloud of moths pours out of {}'s robes",
+                        ai_name.get_text()
+                    ),
+                    MessageColor::White,
+                    MessageDisplayLength::Short,
+                );
+                for summon_position in add_spawn_positions(ai_position, world)
+                    .into_iter()
+                    .take(new_phase as usize)
+                {
+                    create_moth_swarm(summon_position, world);
+                }
+                return;
+            }
+
+            let sight = Targeting::new(1, 8, true, LineOfSight::Piercing);
+            if cast_debuff && can_attack(sight, ai_entity, player_entity, world) {
+                let blind_player = {
+                    let rng = &mut world.fetch_mut::<RNG>().0;
+                    new_phase == 3 && rng.gen_ratio(1, 2)
+                };
+                if blind_player {
+                    world.fetch_mut::<MessageLog>().new_message(
+                        format!(
+                            "{} unleashes a blinding flash. You can't attack for 2 turns",
+                            ai_name.get_text()
+                        ),
+                        MessageColor::Red,
+                        MessageDisplayLength::Medium,
+                    );
+                    apply_status(
+                        player_entity,
+                        StatusEffect::new(StatusKind::CantAttack, 1, Some(2)),
+                        Stacking::Add,
+                        world,
+                    );
+                } else {
+                    world.fetch_mut::<MessageLog>().new_message(
+                        format!(
+                            "{} dazzles you with a glimmering light. Your view shrinks",
+                            ai_name.get_text()
+                        ),
+                        MessageColor::Orange,
+                        MessageDisplayLength::Medium,
+                    );
+                    apply_status(
+                        player_entity,
+                        StatusEffect::new(StatusKind::Dazzled, 1, Some(4)),
+                        Stacking::Refresh,
+                        world,
+                    );
+                }
+                return;
+            }
+
+            if try_attack(
+                4 + new_phase,
+                false,
+                DamageType::Magic,
+                None,
+                Targeting::new(2, 5, true, LineOfSight::FirstEntity),
+                ai_entity,
+                player_entity,
+                world,
+            )
+            .is_err()
+            {
+                if ai_position.chebyshev_distance_from(player_position) <= 3 {
+                    // Keep her distance by stepping to whichever tile is farthest from the player
+                    let mut directions = [
+                        Direction::Up,
+                        Direction::Down,
+                        Direction::Left,
+                        Direction::Right,
+                        Direction::UpLeft,
+                        Direction::DownLeft,
+                        Direction::DownRight,
+                        Direction::UpRight,
+                    ];
+                    directions.sort_by_key(|direction| {
+                        std::cmp::Reverse(
+                            ai_position
+                                .offset_by(*direction)
+                                .distance_from(player_position),
+                        )
+                    });
+                    for direction in &directions {
+                        if try_move(ai_entity, *direction, world).is_ok() {
+                            break;
+                        }
+                    }
+                } else {
+                    let _ = try_move_towards(ai_entity, player_entity, world);
+                }
+            }
         }))
+        .with(Counter(0))
         .with(position)
+        .with(Footprint::new(1))
         .with(attackable)
         .with(Sprite {
-            id: "placeholder",
+            id: "xilphene_the_moth_priestess",
             double_sized: true,
             in_foreground: true,
         })
@@ -384,19 +924,157 @@ pub fn create_xilphene_the_moth_priestess(position: Position, world: &mut World)
 }
 
 pub fn create_ume_the_dungeon_heart(position: Position, world: &mut World) -> Entity {
-    let attackable = Attackable::new(
+    let mut attackable = Attackable::new(
         50,
         200,
         items::create_random_layer1(Rarity::Epic, world),
         true,
     );
+    attackable.is_invulnerable = true;
     world
         .create_entity()
         .with(Name::new("Ume, The Dungeon Heart", false))
         .with(AI::new(|ai_entity, world| {
-            // TODO
+            // Counter(0) = Dormant, Counter(n) = Awake for n turns
+            // Every 12 turns: pulse on turn 0, raise walls and spawners on turn 4, heartbeat open on turns 8-11
+            let (ai_name, ai_position, player_position, turns_awake) = {
+                let name_data = world.read_storage::<Name>();
+                let position_data = world.read_storage::<Position>();
+                let player_data = world.read_storage::<Player>();
+                let mut counter_data = world.write_storage::<Counter>();
+                let player_position = (&player_data, &position_data).join().next().unwrap().1;
+                let ai_position = *position_data.get(ai_entity).unwrap();
+                let ai_counter = counter_data.get_mut(ai_entity).unwrap();
+                if *ai_counter == Counter(0)
+                    && ai_position.chebyshev_distance_from(*player_position) > 8
+                {
+                    return;
+                }
+                ai_counter.0 += 1;
+                (
+                    *name_data.get(ai_entity).unwrap(),
+                    ai_position,
+                    *player_position,
+                    ai_counter.0 - 1,
+                )
+            };
+            if turns_awake == 0 {
+                world.fetch_mut::<MessageLog>().new_message(
+                    format!(
+                        "{}: The dungeon is my body. You are only a splinter in it.",
+                        ai_name.get_text()
+                    ),
+                    MessageColor::White,
+                    MessageDisplayLength::Long,
+                );
+            }
+
+            match turns_awake % 12 {
+                0 => {
+                    {
+                        let mut attackable_data = world.write_storage::<Attackable>();
+                        attackable_data.get_mut(ai_entity).unwrap().is_invulnerable = true;
+                    }
+                    // Alternate between pulsing the area around Ume and the outer edges of the room
+                    let pulse_distances = if (turns_awake / 12) % 2 == 0 {
+                        2..=4
+                    } else {
+                        5..=8
+                    };
+                    let pulse_positions = floor_positions(world)
+                        .into_iter()
+                        .filter(|position| {
+                            pulse_distances
+                                .contains(&ai_position.chebyshev_distance_from(*position))
+                        })
+                        .collect::<Vec<Position>>();
+                    world.fetch_mut::<MessageLog>().new_message(
+                        format!(
+                            "{}'s heartbeat closes. The floor starts to throb...",
+                            ai_name.get_text()
+                        ),
+                        MessageColor::Orange,
+                        MessageDisplayLength::Medium,
+                    );
+                    telegraph_area(
+                        &pulse_positions,
+                        3,
+                        8,
+                        DamageType::Physical,
+                        ai_entity,
+                        world,
+                    );
+                }
+                4 => {
+                    let (wall_positions, spawner_positions) = {
+                        let obstacles = obstacle_positions(world);
+                        let mut free_positions = floor_positions(world)
+                            .into_iter()
+                            .filter(|position| {
+                                !obstacles.contains(position)
+                                    && position.chebyshev_distance_from(player_position) > 1
+                                    && ai_position.chebyshev_distance_from(*position) <= 8
+                            })
+                            .collect::<Vec<Position>>();
+                        free_positions.sort_by_key(|position| (position.x, position.y));
+                        free_positions.shuffle(&mut world.fetch_mut::<RNG>().0);
+                        let spawner_positions = add_spawn_positions(ai_position, world)
+                            .into_iter()
+                            .filter(|position| {
+                                position.chebyshev_distance_from(player_position) > 1
+                            })
+                            .take(2)
+                            .collect::<Vec<Position>>();
+                        let wall_positions = free_positions
+                            .into_iter()
+                            .filter(|position| !spawner_positions.contains(position))
+                            .take(6)
+                            .collect::<Vec<Position>>();
+                        (wall_positions, spawner_positions)
+                    };
+                    world.fetch_mut::<MessageLog>().new_message(
+                        "The walls of the room shift and groan",
+                        MessageColor::White,
+                        MessageDisplayLength::Medium,
+                    );
+                    for wall_position in wall_positions {
+                        let wall = create_wall_with_sprite(wall_position, "wall_mossy", world);
+                        world
+                            .write_storage::<Temporary>()
+                            .insert(wall, Temporary::new(10))
+                            .unwrap();
+                    }
+                    // Short-lived, so they spawn every few turns instead of every 30
+                    for spawner_position in spawner_positions {
+                        let spawner = create_spawner(spawner_position, world);
+                        world
+                            .write_storage::<Spawner>()
+                            .get_mut(spawner)
+                            .unwrap()
+                            .turns_per_spawn = 3;
+                        world
+                            .write_storage::<Temporary>()
+                            .insert(spawner, Temporary::new(6))
+                            .unwrap();
+                    }
+                }
+                8 => {
+                    {
+                        let mut attackable_data = world.write_storage::<Attackable>();
+                        attackable_data.get_mut(ai_entity).unwrap().is_invulnerable = false;
+                    }
+                    world.fetch_mut::<MessageLog>().new_message(
+                        format!("{}'s heartbeat opens. Strike now!", ai_name.get_text()),
+                        MessageColor::Green,
+                        MessageDisplayLength::Medium,
+                    );
+                }
+                _ => {}
+            }
         }))
+        .with(Counter(0))
         .with(position)
+        .with(Footprint::new(1))
         .with(attackable)
         .with(Sprite {
             id: "ume_the_dungeon_heart",
@@ -405,3 +1083,29 @@ pub fn create_ume_the_dungeon_heart(position: Position, world: &mut World) -> En
         })
         .build()
 }
+
+/// Returns free positions for a boss to summon adds on in a random order,
+/// using the spawn points of the boss arena or else the tiles around the boss
+fn add_spawn_positions(boss_position: Position, world: &mut World) -> Vec<Position> {
+    let obstacles = obstacle_positions(world);
+    let mut positions = world
+        .try_fetch::<BossArena>()
+        .map(|boss_arena| boss_arena.add_spawn_positions.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|position| !obstacles.contains(position))
+        .collect::<Vec<Position>>();
+    if positions.is_empty() {
+        positions = Position::new(0, 0)
+            .footprint_positions(Some(&Footprint::new(2)))
+            .into_iter()
+            .map(|offset| Position::new(boss_position.x + offset.x, boss_position.y + offset.y))
+            .filter(|position| {
+                boss_position.chebyshev_distance_from(*position) == 2
+                    && !obstacles.contains(position)
+            })
+            .collect();
+    }
+    positions.shuffle(&mut world.fetch_mut::<RNG>().0);
+    positions
+}
@@ -3,10 +3,99 @@ mod layer1;
 pub use layer1::*;
 
 use crate::data::*;
+use crate::telegraphs::telegraph_area;
 use rand::Rng;
 use rand_pcg::Pcg64;
 use specs::{Builder, Entity, World, WorldExt};
 
+pub type CreateFunction = fn(Position, &mut World) -> Entity;
+
+/// What a map file can change about an entity once it's created
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub enum MapEntityKind {
+    Attackable, // Can have its health set
+    Door,       // Can be open or unlocked
+    Trap,       // Can be revealed
+    Wall,       // Can be damaged
+    Spawner,    // Can spawn faster or slower
+    Other,
+}
+
+/// Looks up how to create any entity besides the player and floors from its name, and what kind of entity it is, used when loading map files
+pub fn create_function_by_name(name: &str) -> Option<(CreateFunction, MapEntityKind)> {
+    let (create_function, kind): (CreateFunction, MapEntityKind) = match name {
+        "Staircase" => (create_staircase, MapEntityKind::Other),
+        "Spawner" => (create_spawner, MapEntityKind::Spawner),
+        "Wall" => (
+            |position, world| create_wall_with_sprite(position, "wall", world),
+            MapEntityKind::Wall,
+        ),
+        "Mossy Wall" => (
+            |position, world| create_wall_with_sprite(position, "wall_mossy", world),
+            MapEntityKind::Wall,
+        ),
+        "Fire Vent" => (create_fire_vent, MapEntityKind::Other),
+        "Door" => (create_door, MapEntityKind::Door),
+        "Locked Door" => (create_locked_door, MapEntityKind::Door),
+        "Secret Wall" => (create_secret_wall, MapEntityKind::Other),
+        "Spike Trap" => (
+            |position, world| create_trap(TrapKind::Spike, position, world),
+            MapEntityKind::Trap,
+        ),
+        "Snare Trap" => (
+            |position, world| create_trap(TrapKind::Snare, position, world),
+            MapEntityKind::Trap,
+        ),
+        "Alarm Trap" => (
+            |position, world| create_trap(TrapKind::Alarm, position, world),
+            MapEntityKind::Trap,
+        ),
+        "Teleport Trap" => (
+            |position, world| create_trap(TrapKind::Teleport, position, world),
+            MapEntityKind::Trap,
+        ),
+        "Sleep Gas Trap" => (
+            |position, world| create_trap(TrapKind::SleepGas, position, world),
+            MapEntityKind::Trap,
+        ),
+        "Shallow Water" => (
+            |position, world| create_terrain(TerrainKind::ShallowWater, position, world),
+            MapEntityKind::Other,
+        ),
+        "Chasm" => (
+            |position, world| create_terrain(TerrainKind::Chasm, position, world),
+            MapEntityKind::Other,
+        ),
+        "Toxic Moss" => (
+            |position, world| create_terrain(TerrainKind::ToxicMoss, position, world),
+            MapEntityKind::Other,
+        ),
+        "Rubble" => (
+            |position, world| create_terrain(TerrainKind::Rubble, position, world),
+            MapEntityKind::Other,
+        ),
+        "Fire" => (create_fire, MapEntityKind::Other),
+        "Phase Bat" => (create_phase_bat, MapEntityKind::Attackable),
+        "Danger! Spider" => (create_danger_spider, MapEntityKind::Attackable),
+        "Pungent Ooze" => (create_pungent_ooze, MapEntityKind::Attackable),
+        "Skeleton Scout" => (create_skeleton_scout, MapEntityKind::Attackable),
+        "Volatile Husk" => (create_volatile_husk, MapEntityKind::Attackable),
+        "Arcane Ooze" => (create_arcane_ooze, MapEntityKind::Attackable),
+        "Soul Spectre" => (create_soul_spectre, MapEntityKind::Attackable),
+        "Discordant Soul" => (create_discordant_soul, MapEntityKind::Attackable),
+        "Lesser Demon" => (create_lesser_demon, MapEntityKind::Attackable),
+        "Moth Swarm" => (create_moth_swarm, MapEntityKind::Attackable),
+        "Siro, King of Hell" => (create_siro_king_of_hell, MapEntityKind::Attackable),
+        "Xilphene, The Moth Priestess" => (
+            create_xilphene_the_moth_priestess,
+            MapEntityKind::Attackable,
+        ),
+        "Ume, The Dungeon Heart" => (create_ume_the_dungeon_heart, MapEntityKind::Attackable),
+        _ => return None,
+    };
+    Some((create_function, kind))
+}
+
 pub fn create_player(world: &mut World) -> Entity {
     let player = Player::new(world);
     world
@@ -40,15 +129,25 @@ pub fn create_spawner(position: Position, world: &mut World) -> Entity {
 }
 
 pub fn create_wall(position: Position, world: &mut World, rng: &mut Pcg64) -> Entity {
+    let sprite_id = if rng.gen_ratio(1, 4) {
+        "wall_mossy"
+    } else {
+        "wall"
+    };
+    create_wall_with_sprite(position, sprite_id, world)
+}
+
+pub fn create_wall_with_sprite(
+    position: Position,
+    sprite_id: &'static str,
+    world: &mut World,
+) -> Entity {
     world
         .create_entity()
         .with(Name::new("Wall", false))
+        .with(Breakable::new(12))
         .with(position)
-        .with(Sprite::new(if rng.gen_ratio(1, 4) {
-            "wall_mossy"
-        } else {
-            "wall"
-        }))
+        .with(Sprite::new(sprite_id))
         .build()
 }
 
@@ -65,3 +164,183 @@ pub fn create_floor(position: Position, world: &mut World) -> Entity {
         })
         .build()
 }
+
+/// Placed instead of a floor
+pub fn create_terrain(kind: TerrainKind, position: Position, world: &mut World) -> Entity {
+    let (name, sprite_id) = match kind {
+        TerrainKind::ShallowWater => ("Shallow Water", "shallow_water"),
+        TerrainKind::Chasm => ("Chasm", "chasm"),
+        TerrainKind::ToxicMoss => ("Toxic Moss", "toxic_moss"),
+        TerrainKind::Rubble => ("Rubble", "rubble"),
+    };
+    world
+        .create_entity()
+        .with(Name::new(name, false))
+        .with(Terrain { kind })
+        .with(position)
+        .with(Intangible {})
+        .with(Sprite {
+            id: sprite_id,
+            double_sized: false,
+            in_foreground: false,
+        })
+        .build()
+}
+
+pub fn create_door(position: Position, world: &mut World) -> Entity {
+    world
+        .create_entity()
+        .with(Name::new("Door", false))
+        .with(Door {
+            is_open: false,
+            is_locked: false,
+        })
+        .with(position)
+        .with(Sprite::new("door_closed"))
+        .build()
+}
+
+pub fn create_locked_door(position: Position, world: &mut World) -> Entity {
+    world
+        .create_entity()
+        .with(Name::new("Locked Door", false))
+        .with(Door {
+            is_open: false,
+            is_locked: true,
+        })
+        .with(position)
+        .with(Sprite::new("door_locked"))
+        .build()
+}
+
+/// Placed on top of a floor, which is uncovered once the secret wall is found
+pub fn create_secret_wall(position: Position, world: &mut World) -> Entity {
+    world
+        .create_entity()
+        .with(Name::new("Wall", false))
+        .with(SecretWall {})
+        .with(position)
+        .with(Sprite::new("wall"))
+        .build()
+}
+
+/// Traps start out hidden, so they don't have a sprite until they're revealed
+pub fn create_trap(kind: TrapKind, position: Position, world: &mut World) -> Entity {
+    let name = match kind {
+        TrapKind::Spike => "Spike Trap",
+        TrapKind::Snare => "Snare Trap",
+        TrapKind::Alarm => "Alarm Trap",
+        TrapKind::Teleport => "Teleport Trap",
+        TrapKind::SleepGas => "Sleep Gas Trap",
+    };
+    world
+        .create_entity()
+        .with(Name::new(name, false))
+        .with(Trap {
+            kind,
+            is_hidden: true,
+        })
+        .with(position)
+        .with(Intangible {})
+        .build()
+}
+
+pub fn create_fire(position: Position, world: &mut World) -> Entity {
+    world
+        .create_entity()
+        .with(Name::new("Fire", false))
+        .with(Fire { turns_left: 6 })
+        .with(position)
+        .with(Intangible {})
+        .with(Sprite {
+            id: "fire",
+            double_sized: false,
+            in_foreground: false,
+        })
+        .build()
+}
+
+/// Drawn over everything else, more see-through the thinner it is
+pub fn create_gas(
+    kind: GasKind,
+    concentration: u32,
+    position: Position,
+    world: &mut World,
+) -> Entity {
+    let (name, sprite_id) = match kind {
+        GasKind::Stench => ("Stench", "stench_gas"),
+        GasKind::Poison => ("Poison Gas", "poison_gas"),
+        GasKind::Sleep => ("Sleep Gas", "sleep_gas"),
+    };
+    world
+        .create_entity()
+        .with(Name::new(name, false))
+        .with(Gas {
+            kind,
+            concentration,
+        })
+        .with(position)
+        .with(Intangible {})
+        .with(Sprite {
+            id: sprite_id,
+            double_sized: false,
+            in_foreground: true,
+        })
+        .build()
+}
+
+pub fn create_telegraph(position: Position, telegraph: Telegraph, world: &mut World) -> Entity {
+    world
+        .create_entity()
+        .with(Name::new("Incoming Attack", false))
+        .with(telegraph)
+        .with(position)
+        .with(Intangible {})
+        .with(Sprite::new("telegraph"))
+        .build()
+}
+
+pub fn create_fire_vent(position: Position, world: &mut World) -> Entity {
+    let turns_until_eruption = {
+        let rng = &mut world.fetch_mut::<RNG>().0;
+        rng.gen_range(1, 7)
+    };
+    world
+        .create_entity()
+        .with(Name::new("Fire Vent", false))
+        .with(AI::new(|ai_entity, world| {
+            let (vent_position, should_erupt) = {
+                let position_data = world.read_storage::<Position>();
+                let mut counter_data = world.write_storage::<Counter>();
+                let vent_counter = counter_data.get_mut(ai_entity).unwrap();
+                vent_counter.0 -= 1;
+                let should_erupt = vent_counter.0 == 0;
+                if should_erupt {
+                    *vent_counter = Counter(6);
+                }
+                (*position_data.get(ai_entity).unwrap(), should_erupt)
+            };
+            if should_erupt {
+                let eruption_positions = [
+                    vent_position,
+                    vent_position.offset_by(Direction::Up),
+                    vent_position.offset_by(Direction::Down),
+                    vent_position.offset_by(Direction::Left),
+                    vent_position.offset_by(Direction::Right),
+                ];
+                telegraph_area(
+                    &eruption_positions,
+                    2,
+                    5,
+                    DamageType::Fire,
+                    ai_entity,
+                    world,
+                );
+            }
+        }))
+        .with(Counter(turns_until_eruption))
+        .with(position)
+        .with(Intangible {})
+        .with(Sprite::new("fire_vent"))
+        .build()
+}
@@ -0,0 +1,176 @@
+use crate::data::*;
+use crate::render::DAZZLED_VIEW_RADIUS;
+use specs::{Entity, Join, World, WorldExt};
+
+/// Returns the lines of text describing everything the player can see at the given position, from the topmost entity down
+pub fn describe_position(position: Position, world: &World) -> Vec<String> {
+    let entities = world.entities();
+    let name_data = world.read_storage::<Name>();
+    let position_data = world.read_storage::<Position>();
+    let sprite_data = world.read_storage::<Sprite>();
+    let attackable_data = world.read_storage::<Attackable>();
+    let item_data = world.read_storage::<Item>();
+    let intangible_data = world.read_storage::<Intangible>();
+    let footprint_data = world.read_storage::<Footprint>();
+    let telegraph_data = world.read_storage::<Telegraph>();
+    let player_data = world.read_storage::<Player>();
+    let status_effects_data = world.read_storage::<StatusEffects>();
+    let triggers_data = world.read_storage::<Triggers>();
+
+    let (player_entity, _, player_position) = (&entities, &player_data, &position_data)
+        .join()
+        .next()
+        .unwrap();
+    if status_effects_data
+        .get(player_entity)
+        .filter(|player_status_effects| player_status_effects.has(StatusKind::Dazzled))
+        .is_some()
+        && player_position.chebyshev_distance_from(position) > DAZZLED_VIEW_RADIUS as u32
+    {
+        return Vec::new();
+    }
+
+    let mut entities_at_position = (
+        &entities,
+        &position_data,
+        &sprite_data,
+        footprint_data.maybe(),
+    )
+        .join()
+        .filter(|(_, entity_position, _, footprint)| {
+            entity_position
+                .footprint_positions(*footprint)
+                .contains(&position)
+        })
+        .map(|(entity, _, _, _)| entity)
+        .collect::<Vec<Entity>>();
+    entities_at_position.sort_by_key(|entity| {
+        if attackable_data.contains(*entity) {
+            0
+        } else if item_data.contains(*entity) {
+            1
+        } else if !intangible_data.contains(*entity) || telegraph_data.contains(*entity) {
+            2
+        } else {
+            3
+        }
+    });
+
+    let mut lines = Vec::new();
+    for entity in entities_at_position {
+        let entity_name = name_data.get(entity).unwrap();
+        let entity_sprite = sprite_data.get(entity).unwrap();
+        if entity_sprite.id == "concealed" {
+            lines.push("???".to_owned());
+        } else if let Some(entity_attackable) = attackable_data.get(entity) {
+            lines.push(entity_name.get_text().to_owned());
+            lines.push(format!(
+                "Health: {}/{}",
+                entity_attackable.current_health, entity_attackable.max_health
+            ));
+            if entity_attackable.is_boss {
+                lines.push("Boss".to_owned());
+            }
+            if let Some(entity_status_effects) = status_effects_data.get(entity) {
+                for effect in entity_status_effects.effects() {
+                    lines.push(effect.kind.describe(&effect));
+                }
+            }
+            if entity_attackable.accuracy != 100 {
+                lines.push(format!("Accuracy: {}%", entity_attackable.accuracy));
+            }
+            if entity_attackable.evasion != 0 {
+                lines.push(format!("Evasion: {}%", entity_attackable.evasion));
+            }
+            let mut resistances = entity_attackable.resistances.iter().collect::<Vec<_>>();
+            resistances.sort_unstable_by_key(|(damage_type, _)| **damage_type);
+            for (damage_type, resistance) in resistances {
+                let damage_type_name = damage_type.name().to_lowercase();
+                lines.push(match resistance {
+                    Resistance::Immune => format!("Immune to {}", damage_type_name),
+                    Resistance::Resistant => format!("Resists {}", damage_type_name),
+                    Resistance::Weak => format!("Weak to {}", damage_type_name),
+                });
+            }
+            if entity_attackable.is_invulnerable {
+                lines.push("Invulnerable".to_owned());
+            }
+            if let Some(entity_triggers) = triggers_data.get(entity) {
+                for description in entity_triggers.descriptions() {
+                    lines.push(description.to_owned());
+                }
+            }
+        } else if item_data.contains(entity) {
+            lines.append(&mut describe_item(entity, world));
+        } else if let Some(telegraph) = telegraph_data.get(entity) {
+            lines.push(entity_name.get_text().to_owned());
+            lines.push(format!(
+                "Hits for {} damage in {} turns",
+                telegraph.damage, telegraph.turns_left
+            ));
+        } else {
+            lines.push(entity_name.get_text().to_owned());
+        }
+    }
+    lines
+}
+
+/// Returns the lines of text describing an item, leaving out anything not yet identified
+pub fn describe_item(item_entity: Entity, world: &World) -> Vec<String> {
+    let name_data = world.read_storage::<Name>();
+    let item_data = world.read_storage::<Item>();
+    let item_name = name_data.get(item_entity).unwrap();
+    let item = item_data.get(item_entity).unwrap();
+
+    let mut lines = vec![item_name.get_text().to_owned()];
+    if item_name.concealed {
+        lines.push("An unidentified scroll. Use it to find out what it does.".to_owned());
+        return lines;
+    }
+
+    lines.push(item.description.to_owned());
+    if item.crystals_per_use == 0 {
+        lines.push("Free to use".to_owned());
+    } else {
+        lines.push(format!("Costs {} crystals per use", item.crystals_per_use));
+    }
+    if let Some((minimum_damage, maximum_damage)) = item.stats.damage {
+        if minimum_damage == maximum_damage {
+            lines.push(format!("Damage: {}", minimum_damage));
+        } else {
+            lines.push(format!("Damage: {}-{}", minimum_damage, maximum_damage));
+        }
+    }
+    if let Some(targeting) = item.stats.targeting {
+        if targeting.minimum_range == targeting.maximum_range {
+            lines.push(format!("Range: {}", targeting.minimum_range));
+        } else {
+            lines.push(format!(
+                "Range: {}-{}",
+                targeting.minimum_range, targeting.maximum_range
+            ));
+        }
+        if targeting.allows_diagonal {
+            lines.push("Can attack diagonally".to_owned());
+        }
+        if targeting.line_of_sight == LineOfSight::Piercing {
+            lines.push("Passes through enemies".to_owned());
+        }
+    }
+    match (item.stats.is_melee, item.stats.damage_type) {
+        (true, DamageType::Physical) => lines.push("Melee".to_owned()),
+        (true, damage_type) => lines.push(format!("Melee, {}", damage_type.name())),
+        (false, DamageType::Physical) => {}
+        (false, damage_type) => lines.push(damage_type.name().to_owned()),
+    }
+    if let Some((critical_hit_chance, critical_hit_multiplier)) = item.stats.critical_hit {
+        lines.push(format!(
+            "{}% chance to crit for x{} damage",
+            critical_hit_chance, critical_hit_multiplier
+        ));
+    }
+    for special_effect in item.stats.special_effects {
+        lines.push((*special_effect).to_owned());
+    }
+    lines
+}
@@ -0,0 +1,177 @@
+use crate::data::*;
+use crate::entities::{create_fire, create_floor};
+use crate::movement::floor_positions;
+use crate::status_effects::apply_status;
+use crate::terrain::terrain_positions;
+use rand::Rng;
+use specs::{Entity, Join, World, WorldExt};
+use std::collections::HashSet;
+
+const BURNING_TURNS: u32 = 3;
+const BURNING_DAMAGE: u32 = 2;
+
+/// Sets fire to every position that can burn, skipping anything already burning
+/// Fire can be lit on any floor, but only spreads to flammable tiles
+pub fn ignite(positions: &[Position], world: &mut World) {
+    let ignitable_positions = {
+        let floor = floor_positions(world);
+        let terrain = terrain_positions(world);
+        let burning_positions = fire_positions(world);
+        positions
+            .iter()
+            .filter(|position| {
+                floor.contains(position)
+                    && !burning_positions.contains(position)
+                    && !matches!(
+                        terrain.get(position),
+                        Some(TerrainKind::ShallowWater) | Some(TerrainKind::Chasm)
+                    )
+            })
+            .copied()
+            .collect::<HashSet<Position>>()
+    };
+    for position in ignitable_positions {
+        create_fire(position, world);
+    }
+}
+
+pub fn fire_positions(world: &World) -> HashSet<Position> {
+    let position_data = world.read_storage::<Position>();
+    let fire_data = world.read_storage::<Fire>();
+    (&position_data, &fire_data)
+        .join()
+        .map(|(position, _)| *position)
+        .collect()
+}
+
+/// Toxic moss, and the floor alongside mossy walls
+fn flammable_positions(world: &World) -> HashSet<Position> {
+    let mossy_walls = mossy_wall_positions(world);
+    let mut flammable_positions = floor_positions(world)
+        .into_iter()
+        .filter(|position| {
+            [
+                Direction::Up,
+                Direction::Down,
+                Direction::Left,
+                Direction::Right,
+            ]
+            .iter()
+            .any(|direction| mossy_walls.contains(&position.offset_by(*direction)))
+        })
+        .collect::<HashSet<Position>>();
+    flammable_positions.extend(
+        terrain_positions(world)
+            .into_iter()
+            .filter(|(_, terrain_kind)| *terrain_kind == TerrainKind::ToxicMoss)
+            .map(|(position, _)| position),
+    );
+    flammable_positions
+}
+
+fn mossy_wall_positions(world: &World) -> HashSet<Position> {
+    let position_data = world.read_storage::<Position>();
+    let sprite_data = world.read_storage::<Sprite>();
+    let intangible_data = world.read_storage::<Intangible>();
+    let attackable_data = world.read_storage::<Attackable>();
+    (
+        &position_data,
+        &sprite_data,
+        !&intangible_data,
+        !&attackable_data,
+    )
+        .join()
+        .filter(|(_, sprite, _, _)| sprite.id == "wall_mossy")
+        .map(|(position, _, _, _)| *position)
+        .collect()
+}
+
+/// Burns down every fire, spreading each one to the flammable tiles next to it,
+/// and burns away the moss under and around the fires that go out so they can't be relit
+pub fn tick_fires(world: &mut World) {
+    let flammable_positions = flammable_positions(world);
+    let burning_positions = fire_positions(world);
+    let (burnt_out_fires, spread_positions) = {
+        let entities = world.entities();
+        let position_data = world.read_storage::<Position>();
+        let mut fire_data = world.write_storage::<Fire>();
+        let rng = &mut world.fetch_mut::<RNG>().0;
+        let mut burnt_out_fires = Vec::new();
+        let mut spread_positions = Vec::new();
+        for (entity, position, fire) in (&entities, &position_data, &mut fire_data).join() {
+            fire.turns_left = fire.turns_left.saturating_sub(1);
+            if fire.turns_left == 0 {
+                burnt_out_fires.push((entity, *position));
+            }
+            for direction in &[
+                Direction::Up,
+                Direction::Down,
+                Direction::Left,
+                Direction::Right,
+            ] {
+                let next_position = position.offset_by(*direction);
+                if flammable_positions.contains(&next_position)
+                    && !burning_positions.contains(&next_position)
+                    && rng.gen_ratio(1, 3)
+                {
+                    spread_positions.push(next_position);
+                }
+            }
+        }
+        (burnt_out_fires, spread_positions)
+    };
+
+    for (fire_entity, position) in burnt_out_fires {
+        world.delete_entity(fire_entity).unwrap();
+        burn_away_moss(position, world);
+    }
+    ignite(&spread_positions, world);
+}
+
+fn burn_away_moss(position: Position, world: &mut World) {
+    let burnt_moss = {
+        let entities = world.entities();
+        let position_data = world.read_storage::<Position>();
+        let terrain_data = world.read_storage::<Terrain>();
+        (&entities, &position_data, &terrain_data)
+            .join()
+            .find(|(_, terrain_position, terrain)| {
+                **terrain_position == position && terrain.kind == TerrainKind::ToxicMoss
+            })
+            .map(|(entity, _, _)| entity)
+    };
+    if let Some(burnt_moss) = burnt_moss {
+        world.delete_entity(burnt_moss).unwrap();
+        create_floor(position, world);
+    }
+
+    let position_data = world.read_storage::<Position>();
+    let mut sprite_data = world.write_storage::<Sprite>();
+    for (wall_position, sprite) in (&position_data, &mut sprite_data).join() {
+        if sprite.id == "wall_mossy" && wall_position.chebyshev_distance_from(position) <= 1 {
+            sprite.id = "wall";
+        }
+    }
+}
+
+/// Sets anything standing in fire alight
+pub fn catch_fire(entity: Entity, burning_positions: &HashSet<Position>, world: &mut World) {
+    let is_in_fire = {
+        let position_data = world.read_storage::<Position>();
+        let footprint_data = world.read_storage::<Footprint>();
+        position_data
+            .get(entity)
+            .unwrap()
+            .footprint_positions(footprint_data.get(entity))
+            .iter()
+            .any(|position| burning_positions.contains(position))
+    };
+    if is_in_fire {
+        apply_status(
+            entity,
+            StatusEffect::new(StatusKind::Burning, BURNING_DAMAGE, Some(BURNING_TURNS)),
+            Stacking::Refresh,
+            world,
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use crate::data::*;
+use crate::entities::create_gas;
+use crate::movement::floor_positions;
+use crate::status_effects::{apply_status, status_stacks};
+use specs::{Entity, Join, World, WorldExt};
+use std::collections::HashMap;
+
+const SPREAD_DIVISOR: u32 = 8; // Each open neighbor gets this fraction of a tile's gas every turn
+const DECAY_PER_TURN: u32 = 1;
+const MINIMUM_CONCENTRATION: u32 = 2;
+const EFFECT_CONCENTRATION: u32 = 10;
+const STENCH_MAXIMUM_OOZED_STACKS: u32 = 3;
+const POISON_BLIGHT_TURNS: u32 = 2;
+const SLEEP_TURNS: u32 = 2;
+
+/// Adds gas to the tile, joining any gas of the same kind that's already there
+pub fn release_gas(kind: GasKind, position: Position, concentration: u32, world: &mut World) {
+    let existing_gas = {
+        let entities = world.entities();
+        let position_data = world.read_storage::<Position>();
+        let gas_data = world.read_storage::<Gas>();
+        (&entities, &position_data, &gas_data)
+            .join()
+            .find(|(_, gas_position, gas)| **gas_position == position && gas.kind == kind)
+            .map(|(entity, _, _)| entity)
+    };
+    match existing_gas {
+        Some(gas_entity) => {
+            let mut gas_data = world.write_storage::<Gas>();
+            gas_data.get_mut(gas_entity).unwrap().concentration += concentration;
+        }
+        None => {
+            create_gas(kind, concentration, position, world);
+        }
+    }
+}
+
+/// Spreads every gas cloud out across the open tiles around it, then thins them all out,
+/// removing the gas that's too thin to matter
+pub fn tick_gas(world: &mut World) {
+    let open_positions = floor_positions(world);
+    let mut gas_entities = HashMap::new();
+    let mut new_concentrations = HashMap::new();
+    {
+        let entities = world.entities();
+        let position_data = world.read_storage::<Position>();
+        let gas_data = world.read_storage::<Gas>();
+        for (entity, position, gas) in (&entities, &position_data, &gas_data).join() {
+            gas_entities.insert((*position, gas.kind), entity);
+            let spread_positions = [
+                Direction::Up,
+                Direction::Down,
+                Direction::Left,
+                Direction::Right,
+            ]
+            .iter()
+            .map(|direction| position.offset_by(*direction))
+            .filter(|next_position| open_positions.contains(next_position))
+            .collect::<Vec<Position>>();
+            let spread_concentration = gas.concentration / SPREAD_DIVISOR;
+            *new_concentrations.entry((*position, gas.kind)).or_insert(0) +=
+                gas.concentration - spread_concentration * spread_positions.len() as u32;
+            for next_position in spread_positions {
+                *new_concentrations
+                    .entry((next_position, gas.kind))
+                    .or_insert(0) += spread_concentration;
+            }
+        }
+    }
+
+    for ((position, kind), concentration) in new_concentrations {
+        let concentration = concentration.saturating_sub(DECAY_PER_TURN);
+        match gas_entities.remove(&(position, kind)) {
+            Some(gas_entity) if concentration >= MINIMUM_CONCENTRATION => {
+                let mut gas_data = world.write_storage::<Gas>();
+                gas_data.get_mut(gas_entity).unwrap().concentration = concentration;
+            }
+            Some(gas_entity) => {
+                world.delete_entity(gas_entity).unwrap();
+            }
+            None if concentration >= MINIMUM_CONCENTRATION => {
+                create_gas(kind, concentration, position, world);
+            }
+            None => {}
+        }
+    }
+}
+
+/// Returns how concentrated each kind of gas is on every tile with gas on it
+pub fn gas_positions(world: &World) -> HashMap<(Position, GasKind), u32> {
+    let position_data = world.read_storage::<Position>();
+    let gas_data = world.read_storage::<Gas>();
+    (&position_data, &gas_data)
+        .join()
+        .map(|(position, gas)| ((*position, gas.kind), gas.concentration))
+        .collect()
+}
+
+/// Applies the effects of every gas thick enough to matter around the entity
+pub fn breathe_gas(
+    entity: Entity,
+    gas_positions: &HashMap<(Position, GasKind), u32>,
+    world: &mut World,
+) {
+    let entity_positions = {
+        let position_data = world.read_storage::<Position>();
+        let footprint_data = world.read_storage::<Footprint>();
+        position_data
+            .get(entity)
+            .unwrap()
+            .footprint_positions(footprint_data.get(entity))
+    };
+    let is_in_gas = |kind: GasKind| {
+        entity_positions.iter().any(|position| {
+            gas_positions
+                .get(&(*position, kind))
+                .filter(|concentration| **concentration >= EFFECT_CONCENTRATION)
+                .is_some()
+        })
+    };
+
+    let ignores_stench = world.read_storage::<IgnoresStench>().contains(entity);
+    if is_in_gas(GasKind::Stench)
+        && !ignores_stench
+        && status_stacks(entity, StatusKind::Oozed, world) < STENCH_MAXIMUM_OOZED_STACKS
+    {
+        apply_status(
+            entity,
+            StatusEffect::new(StatusKind::Oozed, 1, None),
+            Stacking::Add,
+            world,
+        );
+    }
+    if is_in_gas(GasKind::Poison) {
+        apply_status(
+            entity,
+            StatusEffect::new(StatusKind::Blight, 1, Some(POISON_BLIGHT_TURNS)),
+            Stacking::Refresh,
+            world,
+        );
+    }
+    if is_in_gas(GasKind::Sleep) {
+        for kind in &[StatusKind::CantMove, StatusKind::CantAttack] {
+            apply_status(
+                entity,
+                StatusEffect::new(*kind, 1, Some(SLEEP_TURNS)),
+                Stacking::Refresh,
+                world,
+            );
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::data::Position;
+use crate::generators::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_pcg::Pcg64;
+use std::collections::HashSet;
+
+const MAP_RADIUS: i16 = 32;
+const MINIMUM_REGION_SIZE: i16 = 10;
+
+/// Recursively splits the map in two, puts a room in every leaf and connects sibling regions
+pub struct BspGenerator;
+
+impl DungeonGenerator for BspGenerator {
+    fn name(&self) -> &'static str {
+        "BSP"
+    }
+
+    fn generate(&self, rng: &mut Pcg64) -> GeneratedFloor {
+        let mut rooms = Vec::new();
+        let mut floor_positions = HashSet::new();
+        split_region(
+            Region {
+                min: Position::new(-MAP_RADIUS, -MAP_RADIUS),
+                max: Position::new(MAP_RADIUS, MAP_RADIUS),
+            },
+            &mut rooms,
+            &mut floor_positions,
+            rng,
+        );
+
+        let start_position = rooms.choose(rng).unwrap().center;
+        GeneratedFloor::from_floor(floor_positions, start_position, 3, rng)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Region {
+    min: Position,
+    max: Position,
+}
+
+/// Carves the rooms for a region and returns the room its corridors should connect to
+fn split_region(
+    region: Region,
+    rooms: &mut Vec<Room>,
+    floor_positions: &mut HashSet<Position>,
+    rng: &mut Pcg64,
+) -> Room {
+    let width = region.max.x - region.min.x;
+    let height = region.max.y - region.min.y;
+    let can_split_vertically = width >= MINIMUM_REGION_SIZE * 2;
+    let can_split_horizontally = height >= MINIMUM_REGION_SIZE * 2;
+    let split_vertically = match (can_split_vertically, can_split_horizontally) {
+        (true, true) => width > height || (width == height && rng.gen()),
+        (true, false) => true,
+        (false, true) => false,
+        (false, false) => {
+            // Leave at least one tile between the room and the edge of the region for walls
+            let x_radius = rng.gen_range(2, (width - 2) / 2);
+            let y_radius = rng.gen_range(2, (height - 2) / 2);
+            let room = Room {
+                center: Position::new(
+                    rng.gen_range(region.min.x + x_radius + 1, region.max.x - x_radius),
+                    rng.gen_range(region.min.y + y_radius + 1, region.max.y - y_radius),
+                ),
+                x_radius: x_radius as u16,
+                y_radius: y_radius as u16,
+            };
+            floor_positions.extend(room.positions());
+            rooms.push(room);
+            return room;
+        }
+    };
+
+    let (first_region, second_region) = if split_vertically {
+        let split_x = rng.gen_range(
+            region.min.x + MINIMUM_REGION_SIZE,
+            region.max.x - MINIMUM_REGION_SIZE + 1,
+        );
+        (
+            Region {
+                min: region.min,
+                max: Position::new(split_x, region.max.y),
+            },
+            Region {
+                min: Position::new(split_x, region.min.y),
+                max: region.max,
+            },
+        )
+    } else {
+        let split_y = rng.gen_range(
+            region.min.y + MINIMUM_REGION_SIZE,
+            region.max.y - MINIMUM_REGION_SIZE + 1,
+        );
+        (
+            Region {
+                min: region.min,
+                max: Position::new(region.max.x, split_y),
+            },
+            Region {
+                min: Position::new(region.min.x, split_y),
+                max: region.max,
+            },
+        )
+    };
+    let first_room = split_region(first_region, rooms, floor_positions, rng);
+    let second_room = split_region(second_region, rooms, floor_positions, rng);
+    floor_positions.extend(corridor_positions(first_room.center, second_room.center));
+    *[first_room, second_room].choose(rng).unwrap()
+}
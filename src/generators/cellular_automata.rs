@@ -0,0 +1,63 @@
+use crate::data::Position;
+use crate::generators::*;
+use rand::Rng;
+use rand_pcg::Pcg64;
+use std::collections::HashSet;
+
+const MAP_RADIUS: i16 = 30;
+const SMOOTHING_STEPS: u32 = 5;
+
+/// Fills the map with random noise and smooths it into caverns, keeping the cavern connected to the middle of the map
+pub struct CellularAutomataGenerator;
+
+impl DungeonGenerator for CellularAutomataGenerator {
+    fn name(&self) -> &'static str {
+        "Cellular Automata"
+    }
+
+    fn generate(&self, rng: &mut Pcg64) -> GeneratedFloor {
+        let mut floor_positions = HashSet::new();
+        for x in -MAP_RADIUS..=MAP_RADIUS {
+            for y in -MAP_RADIUS..=MAP_RADIUS {
+                let position = Position::new(x, y);
+                if position.chebyshev_distance_from(Position::new(0, 0)) <= 2
+                    || !rng.gen_ratio(45, 100)
+                {
+                    floor_positions.insert(position);
+                }
+            }
+        }
+
+        for _ in 0..SMOOTHING_STEPS {
+            let mut next_floor_positions = HashSet::with_capacity(floor_positions.len());
+            for x in -MAP_RADIUS + 1..MAP_RADIUS {
+                for y in -MAP_RADIUS + 1..MAP_RADIUS {
+                    let position = Position::new(x, y);
+                    let neighboring_walls = 8 - position
+                        .neighbors(&HashSet::new())
+                        .iter()
+                        .filter(|neighbor| floor_positions.contains(neighbor))
+                        .count();
+                    let is_wall = if floor_positions.contains(&position) {
+                        neighboring_walls >= 5
+                    } else {
+                        neighboring_walls >= 4
+                    };
+                    if !is_wall {
+                        next_floor_positions.insert(position);
+                    }
+                }
+            }
+            floor_positions = next_floor_positions;
+        }
+
+        // Keep only the cavern connected to the start, so there are no unreachable pockets
+        let start_position = Position::new(0, 0);
+        floor_positions.insert(start_position);
+        let floor_positions = distances_from(start_position, &floor_positions)
+            .keys()
+            .copied()
+            .collect();
+        GeneratedFloor::from_floor(floor_positions, start_position, 3, rng)
+    }
+}
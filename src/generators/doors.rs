@@ -0,0 +1,86 @@
+use crate::data::Position;
+use crate::generators::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_pcg::Pcg64;
+use std::collections::HashSet;
+
+const MAXIMUM_DOORS: usize = 8;
+
+impl GeneratedFloor {
+    /// Puts doors in up to 8 of the doorways leading into rooms, turning a few of the ones
+    /// that aren't needed to get around the floor into secret walls
+    pub fn place_doors(&mut self, rng: &mut Pcg64) {
+        let mut doorways = self
+            .floor_positions
+            .iter()
+            .filter(|position| self.is_doorway(**position))
+            .copied()
+            .collect::<Vec<Position>>();
+        doorways.sort_by_key(|position| (position.x, position.y));
+        doorways.shuffle(rng);
+
+        let mut door_positions = HashSet::new();
+        for doorway in doorways {
+            if door_positions.len() >= MAXIMUM_DOORS {
+                break;
+            }
+            if doorway
+                .neighbors(&HashSet::new())
+                .iter()
+                .any(|neighbor| door_positions.contains(neighbor))
+            {
+                continue;
+            }
+            door_positions.insert(doorway);
+            if rng.gen_ratio(1, 5) && !self.is_needed_for_connectivity(doorway) {
+                self.features.push((doorway, FloorFeature::SecretWall));
+            } else {
+                self.features.push((doorway, FloorFeature::Door));
+            }
+        }
+    }
+
+    /// A floor tile walled in on two opposite sides, with at least one of its open sides leading into a wider area
+    fn is_doorway(&self, position: Position) -> bool {
+        if position == self.start_position
+            || position == self.staircase_position
+            || self.spawner_positions.contains(&position)
+            || self.vault_positions.contains(&position)
+            || self.terrain.contains_key(&position)
+            || self
+                .features
+                .iter()
+                .any(|(feature_position, _)| *feature_position == position)
+        {
+            return false;
+        }
+
+        let is_floor = |x_offset: i16, y_offset: i16| {
+            self.floor_positions
+                .contains(&Position::new(position.x + x_offset, position.y + y_offset))
+        };
+        let is_walled_in =
+            (!is_floor(-1, 0) && !is_floor(1, 0) && is_floor(0, 1) && is_floor(0, -1))
+                || (!is_floor(0, 1) && !is_floor(0, -1) && is_floor(-1, 0) && is_floor(1, 0));
+        let diagonal_floor_count = [(-1, 1), (1, 1), (-1, -1), (1, -1)]
+            .iter()
+            .filter(|(x_offset, y_offset)| is_floor(*x_offset, *y_offset))
+            .count();
+        is_walled_in && diagonal_floor_count >= 2
+    }
+
+    /// Whether some of the floor can only be reached from the start through this position,
+    /// without going through any of the secret walls that have already been placed or any chasms
+    fn is_needed_for_connectivity(&self, position: Position) -> bool {
+        let mut floor_positions = self.walkable_positions();
+        for (feature_position, feature) in &self.features {
+            if *feature == FloorFeature::SecretWall {
+                floor_positions.remove(feature_position);
+            }
+        }
+        let reachable_count = distances_from(self.start_position, &floor_positions).len();
+        floor_positions.remove(&position);
+        distances_from(self.start_position, &floor_positions).len() + 1 < reachable_count
+    }
+}
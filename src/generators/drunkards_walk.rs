@@ -0,0 +1,40 @@
+use crate::data::Position;
+use crate::generators::*;
+use rand::seq::SliceRandom;
+use rand_pcg::Pcg64;
+use std::collections::HashSet;
+
+const FLOOR_TILES: usize = 700;
+const STEPS_PER_WALKER: u32 = 200;
+
+/// Sends walkers stumbling out from already carved tunnels until enough of the floor has been dug
+pub struct DrunkardsWalkGenerator;
+
+impl DungeonGenerator for DrunkardsWalkGenerator {
+    fn name(&self) -> &'static str {
+        "Drunkard's Walk"
+    }
+
+    fn generate(&self, rng: &mut Pcg64) -> GeneratedFloor {
+        let start_position = Position::new(0, 0);
+        let mut floor_positions = HashSet::with_capacity(FLOOR_TILES);
+        floor_positions.insert(start_position);
+        // Walkers start from tiles in the order they were dug, since the HashSet's order changes between runs
+        let mut dug_positions = vec![start_position];
+        while floor_positions.len() < FLOOR_TILES {
+            let mut walker_position = *dug_positions.choose(rng).unwrap();
+            for _ in 0..STEPS_PER_WALKER {
+                let (x_offset, y_offset) = *[(1, 0), (-1, 0), (0, 1), (0, -1)].choose(rng).unwrap();
+                walker_position =
+                    Position::new(walker_position.x + x_offset, walker_position.y + y_offset);
+                if floor_positions.insert(walker_position) {
+                    dug_positions.push(walker_position);
+                }
+                if floor_positions.len() >= FLOOR_TILES {
+                    break;
+                }
+            }
+        }
+        GeneratedFloor::from_floor(floor_positions, start_position, 3, rng)
+    }
+}
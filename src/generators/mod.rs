@@ -0,0 +1,166 @@
+mod bsp;
+mod cellular_automata;
+mod doors;
+mod drunkards_walk;
+mod rooms;
+mod terrain;
+mod traps;
+mod validation;
+mod vaults;
+
+pub use bsp::BspGenerator;
+pub use cellular_automata::CellularAutomataGenerator;
+pub use drunkards_walk::DrunkardsWalkGenerator;
+pub use rooms::RoomsGenerator;
+pub use validation::run_generator_test_harness;
+pub use vaults::Vault;
+
+use crate::data::{Position, Rarity, TerrainKind, TrapKind};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_pcg::Pcg64;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+pub trait DungeonGenerator {
+    fn name(&self) -> &'static str;
+    fn generate(&self, rng: &mut Pcg64) -> GeneratedFloor;
+}
+
+/// What every generator hands back to GenerateDungeonSystem to be turned into entities
+#[derive(Debug, Clone)]
+pub struct GeneratedFloor {
+    pub floor_positions: HashSet<Position>,
+    pub wall_positions: HashSet<Position>,
+    pub start_position: Position,
+    pub staircase_position: Position,
+    pub spawner_positions: Vec<Position>,
+    pub features: Vec<(Position, FloorFeature)>,
+    pub vault_positions: HashSet<Position>, // Every tile of every stamped vault, walls included
+    pub terrain: HashMap<Position, TerrainKind>, // Placed instead of the floor on these positions
+}
+
+/// Something placed on top of the floor, usually by a vault
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub enum FloorFeature {
+    Item(Rarity),
+    Enemy(Rarity),
+    ConcealedEnemy(Rarity),
+    Door,
+    LockedDoor,
+    SecretWall,
+    Key,
+    Trap(TrapKind),
+}
+
+impl GeneratedFloor {
+    /// Walls every tile next to the floor, and picks the staircase and spawners from the positions reachable from the start
+    pub fn from_floor(
+        floor_positions: HashSet<Position>,
+        start_position: Position,
+        spawner_count: usize,
+        rng: &mut Pcg64,
+    ) -> Self {
+        let distances = distances_from(start_position, &floor_positions);
+        let staircase_position = *distances
+            .iter()
+            .max_by_key(|(position, distance)| (**distance, position.x, position.y))
+            .unwrap()
+            .0;
+        let mut spawner_choices = distances
+            .iter()
+            .filter(|(position, distance)| **distance >= 8 && **position != staircase_position)
+            .map(|(position, _)| *position)
+            .collect::<Vec<Position>>();
+        spawner_choices.sort_by_key(|position| (position.x, position.y));
+        let spawner_positions = spawner_choices
+            .choose_multiple(rng, spawner_count)
+            .copied()
+            .collect();
+        Self {
+            wall_positions: walls_around(&floor_positions),
+            floor_positions,
+            start_position,
+            staircase_position,
+            spawner_positions,
+            features: Vec::new(),
+            vault_positions: HashSet::new(),
+            terrain: HashMap::new(),
+        }
+    }
+}
+
+/// Returns every non-floor position touching the floor, including diagonally
+pub fn walls_around(floor_positions: &HashSet<Position>) -> HashSet<Position> {
+    floor_positions
+        .iter()
+        .flat_map(|position| position.neighbors(floor_positions))
+        .collect()
+}
+
+/// Walking distances from the start to every floor position connected to it
+pub fn distances_from(
+    start_position: Position,
+    floor_positions: &HashSet<Position>,
+) -> HashMap<Position, u32> {
+    let mut distances = HashMap::new();
+    let mut frontier = VecDeque::new();
+    if floor_positions.contains(&start_position) {
+        distances.insert(start_position, 0);
+        frontier.push_back(start_position);
+    }
+    while let Some(visiting) = frontier.pop_front() {
+        for next in visiting.neighbors(&HashSet::new()) {
+            if floor_positions.contains(&next) && !distances.contains_key(&next) {
+                distances.insert(next, distances[&visiting] + 1);
+                frontier.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Room {
+    center: Position,
+    x_radius: u16,
+    y_radius: u16,
+}
+
+impl Room {
+    fn positions(&self) -> Vec<Position> {
+        let x_radius = self.x_radius as i16;
+        let y_radius = self.y_radius as i16;
+        let mut positions = Vec::new();
+        for x in -x_radius..=x_radius {
+            for y in -y_radius..=y_radius {
+                positions.push(Position::new(self.center.x + x, self.center.y + y));
+            }
+        }
+        positions
+    }
+
+    fn random_position(&self, rng: &mut Pcg64) -> Position {
+        Position::new(
+            rng.gen_range(
+                self.center.x - self.x_radius as i16,
+                self.center.x + self.x_radius as i16 + 1,
+            ),
+            rng.gen_range(
+                self.center.y - self.y_radius as i16,
+                self.center.y + self.y_radius as i16 + 1,
+            ),
+        )
+    }
+}
+
+/// Returns the positions of an L-shaped corridor, going horizontally first
+fn corridor_positions(start: Position, end: Position) -> Vec<Position> {
+    let mut positions = Vec::new();
+    for x in start.x.min(end.x)..start.x.max(end.x) {
+        positions.push(Position::new(x, start.y));
+    }
+    for y in start.y.min(end.y)..=start.y.max(end.y) {
+        positions.push(Position::new(end.x, y));
+    }
+    positions
+}
@@ -0,0 +1,89 @@
+use crate::data::Position;
+use crate::generators::*;
+use rand::Rng;
+use rand_pcg::Pcg64;
+use std::collections::{HashMap, HashSet};
+
+/// Scatters non-overlapping rectangular rooms and connects each one to a random other room
+pub struct RoomsGenerator;
+
+impl DungeonGenerator for RoomsGenerator {
+    fn name(&self) -> &'static str {
+        "Rooms"
+    }
+
+    fn generate(&self, rng: &mut Pcg64) -> GeneratedFloor {
+        let mut rooms = Vec::with_capacity(41);
+        let starting_room = Room {
+            center: Position::new(0, 0),
+            x_radius: 3,
+            y_radius: 3,
+        };
+        rooms.push(starting_room);
+        'room_placing_loop: for _ in 0..200 {
+            let room = Room {
+                center: Position::new(rng.gen_range(-30, 31), rng.gen_range(-30, 31)),
+                x_radius: rng.gen_range(2, 8),
+                y_radius: rng.gen_range(2, 8),
+            };
+            for other_room in &rooms {
+                let required_gap = rng.gen_range(3, 10);
+                let x_gap = (room.center.x - other_room.center.x).abs()
+                    - room.x_radius as i16
+                    - other_room.x_radius as i16
+                    - 3;
+                let y_gap = (room.center.y - other_room.center.y).abs()
+                    - room.y_radius as i16
+                    - other_room.y_radius as i16
+                    - 3;
+                let actual_gap = x_gap.max(y_gap);
+                if actual_gap < required_gap && actual_gap != -1 {
+                    continue 'room_placing_loop;
+                }
+            }
+            rooms.push(room);
+        }
+
+        let mut floor_positions = HashSet::new();
+        for room in &rooms {
+            floor_positions.extend(room.positions());
+        }
+        for (start_room_index, start_room) in rooms.iter().enumerate() {
+            let mut end_room_index = rng.gen_range(0, rooms.len());
+            while end_room_index == start_room_index {
+                end_room_index = rng.gen_range(0, rooms.len());
+            }
+            let end_room = &rooms[end_room_index];
+            let start = start_room.random_position(rng);
+            let end = end_room.random_position(rng);
+            floor_positions.extend(corridor_positions(start, end));
+        }
+
+        let staircase_room = &rooms[1];
+        let staircase_position = Room {
+            center: staircase_room.center,
+            x_radius: staircase_room.x_radius - 1,
+            y_radius: staircase_room.y_radius - 1,
+        }
+        .random_position(rng);
+
+        let mut spawner_positions = Vec::with_capacity(3);
+        for room in rooms.iter().skip(1).take(3) {
+            let spawner_position = room.random_position(rng);
+            if spawner_position != staircase_position {
+                spawner_positions.push(spawner_position);
+            }
+        }
+
+        GeneratedFloor {
+            wall_positions: walls_around(&floor_positions),
+            floor_positions,
+            start_position: starting_room.center,
+            staircase_position,
+            spawner_positions,
+            features: Vec::new(),
+            vault_positions: HashSet::new(),
+            terrain: HashMap::new(),
+        }
+    }
+}
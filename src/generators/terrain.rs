@@ -0,0 +1,113 @@
+use crate::data::{Position, TerrainKind};
+use crate::generators::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_pcg::Pcg64;
+use std::collections::HashSet;
+
+const SAFE_DISTANCE_FROM_START: u32 = 2;
+
+#[derive(Debug, Copy, Clone)]
+enum TerrainTheme {
+    Flooded,   // Shallow water
+    Overgrown, // Toxic moss
+    Collapsed, // Rubble around a chasm
+}
+
+impl GeneratedFloor {
+    /// Grows 2 to 4 patches of themed terrain out from random floor tiles,
+    /// leaving the start, anything placed on the floor and the area around vaults alone
+    pub fn place_terrain(&mut self, rng: &mut Pcg64) {
+        let mut seed_choices = self
+            .floor_positions
+            .iter()
+            .filter(|position| self.can_have_terrain(**position))
+            .copied()
+            .collect::<Vec<Position>>();
+        seed_choices.sort_by_key(|position| (position.x, position.y));
+
+        let patch_count = rng.gen_range(2, 5);
+        for seed in seed_choices.choose_multiple(rng, patch_count) {
+            if self.terrain.contains_key(seed) {
+                continue;
+            }
+            let theme = *[
+                TerrainTheme::Flooded,
+                TerrainTheme::Overgrown,
+                TerrainTheme::Collapsed,
+            ]
+            .choose(rng)
+            .unwrap();
+            let patch_size = rng.gen_range(6, 21);
+            for position in self.grow_patch(*seed, patch_size, rng) {
+                let terrain_kind = match theme {
+                    TerrainTheme::Flooded => TerrainKind::ShallowWater,
+                    TerrainTheme::Overgrown => TerrainKind::ToxicMoss,
+                    TerrainTheme::Collapsed
+                        if position.chebyshev_distance_from(*seed) <= 1
+                            && !self.is_needed_for_walking(position) =>
+                    {
+                        TerrainKind::Chasm
+                    }
+                    TerrainTheme::Collapsed => TerrainKind::Rubble,
+                };
+                self.terrain.insert(position, terrain_kind);
+            }
+        }
+    }
+
+    /// Spreads out from the seed in random directions over floor that can have terrain on it
+    fn grow_patch(&self, seed: Position, patch_size: usize, rng: &mut Pcg64) -> Vec<Position> {
+        let mut patch = vec![seed];
+        let mut visited = HashSet::new();
+        visited.insert(seed);
+        let mut frontier = vec![seed];
+        while patch.len() < patch_size && !frontier.is_empty() {
+            let visiting = frontier.swap_remove(rng.gen_range(0, frontier.len()));
+            for offset in &[(0, 1), (0, -1), (1, 0), (-1, 0)] {
+                let next = Position::new(visiting.x + offset.0, visiting.y + offset.1);
+                if visited.insert(next)
+                    && self.floor_positions.contains(&next)
+                    && self.can_have_terrain(next)
+                    && !self.terrain.contains_key(&next)
+                    && patch.len() < patch_size
+                {
+                    patch.push(next);
+                    frontier.push(next);
+                }
+            }
+        }
+        patch
+    }
+
+    fn can_have_terrain(&self, position: Position) -> bool {
+        position.chebyshev_distance_from(self.start_position) > SAFE_DISTANCE_FROM_START
+            && position != self.staircase_position
+            && !self.spawner_positions.contains(&position)
+            && !self
+                .features
+                .iter()
+                .any(|(feature_position, _)| *feature_position == position)
+            && !self
+                .vault_positions
+                .iter()
+                .any(|vault_position| vault_position.chebyshev_distance_from(position) <= 1)
+    }
+
+    /// Whether turning this position into a chasm would cut off some of the floor from the start
+    fn is_needed_for_walking(&self, position: Position) -> bool {
+        let mut walkable_positions = self.walkable_positions();
+        let reachable_count = distances_from(self.start_position, &walkable_positions).len();
+        walkable_positions.remove(&position);
+        distances_from(self.start_position, &walkable_positions).len() + 1 < reachable_count
+    }
+
+    /// Every floor position that isn't a chasm
+    pub fn walkable_positions(&self) -> HashSet<Position> {
+        self.floor_positions
+            .iter()
+            .filter(|position| self.terrain.get(position) != Some(&TerrainKind::Chasm))
+            .copied()
+            .collect()
+    }
+}
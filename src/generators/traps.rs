@@ -0,0 +1,48 @@
+use crate::data::{Position, TrapKind};
+use crate::generators::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_pcg::Pcg64;
+
+const SAFE_DISTANCE_FROM_START: u32 = 4;
+
+impl GeneratedFloor {
+    /// Hides a few random traps around the floor, away from the start and anything else placed on it
+    pub fn place_traps(&mut self, rng: &mut Pcg64) {
+        let mut trap_choices = self
+            .floor_positions
+            .iter()
+            .filter(|position| {
+                position.chebyshev_distance_from(self.start_position) > SAFE_DISTANCE_FROM_START
+                    && **position != self.staircase_position
+                    && !self.spawner_positions.contains(position)
+                    && !self.vault_positions.contains(position)
+                    && !self.terrain.contains_key(position)
+                    && !self
+                        .features
+                        .iter()
+                        .any(|(feature_position, _)| feature_position == *position)
+            })
+            .copied()
+            .collect::<Vec<Position>>();
+        trap_choices.sort_by_key(|position| (position.x, position.y));
+        let trap_count = rng.gen_range(3, 7);
+        for trap_position in trap_choices.choose_multiple(rng, trap_count) {
+            self.features
+                .push((*trap_position, FloorFeature::Trap(random_trap_kind(rng))));
+        }
+    }
+}
+
+pub fn random_trap_kind(rng: &mut Pcg64) -> TrapKind {
+    [
+        (TrapKind::Spike, 40),
+        (TrapKind::Snare, 30),
+        (TrapKind::Teleport, 20),
+        (TrapKind::SleepGas, 15),
+        (TrapKind::Alarm, 10),
+    ]
+    .choose_weighted(rng, |choice| choice.1)
+    .unwrap()
+    .0
+}
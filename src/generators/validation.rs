@@ -0,0 +1,212 @@
+use crate::data::Position;
+use crate::generators::*;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+const MAXIMUM_REPAIRS: u32 = 50;
+
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+pub enum ConnectivityError {
+    StartNotOnFloor,
+    UnreachableStaircase(Position),
+    UnreachableSpawner(Position),
+    DisconnectedFloor(usize), // Number of floor positions that can't be reached from the start
+}
+
+impl GeneratedFloor {
+    /// Flood fills from the start position without crossing chasms and returns everything that can't be reached
+    pub fn validate(&self) -> Result<(), Vec<ConnectivityError>> {
+        if !self.floor_positions.contains(&self.start_position) {
+            return Err(vec![ConnectivityError::StartNotOnFloor]);
+        }
+
+        let walkable_positions = self.walkable_positions();
+        let reachable_positions = distances_from(self.start_position, &walkable_positions);
+        let mut errors = Vec::new();
+        if !reachable_positions.contains_key(&self.staircase_position) {
+            errors.push(ConnectivityError::UnreachableStaircase(
+                self.staircase_position,
+            ));
+        }
+        for spawner_position in &self.spawner_positions {
+            if !reachable_positions.contains_key(spawner_position) {
+                errors.push(ConnectivityError::UnreachableSpawner(*spawner_position));
+            }
+        }
+        let unreachable_floor_count = walkable_positions.len() - reachable_positions.len();
+        if unreachable_floor_count != 0 {
+            errors.push(ConnectivityError::DisconnectedFloor(
+                unreachable_floor_count,
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Connects every part of the floor that can't be reached from the start by carving a corridor
+    /// from its closest position to the closest reachable position, then rebuilds the walls
+    /// Corridors avoid cutting through the walls of vaults when they can, so doors into vaults can't be skipped
+    pub fn repair(&mut self) {
+        if !self.floor_positions.contains(&self.start_position) {
+            return;
+        }
+
+        for _ in 0..MAXIMUM_REPAIRS {
+            let reachable_positions = distances_from(self.start_position, &self.floor_positions)
+                .keys()
+                .copied()
+                .collect::<HashSet<Position>>();
+            let mut unreachable_positions = self
+                .floor_positions
+                .difference(&reachable_positions)
+                .copied()
+                .collect::<Vec<Position>>();
+            if unreachable_positions.is_empty() {
+                break;
+            }
+            unreachable_positions.sort_by_key(|position| (position.x, position.y));
+
+            // Only connect the first disconnected component each time, since carving can merge several at once
+            let mut component_positions =
+                distances_from(unreachable_positions[0], &self.floor_positions)
+                    .keys()
+                    .copied()
+                    .collect::<Vec<Position>>();
+            // Sorted so the search around vaults visits them in the same order for the same seed
+            component_positions.sort_by_key(|position| (position.x, position.y));
+            let (start, end) = component_positions
+                .iter()
+                .flat_map(|component_position| {
+                    reachable_positions
+                        .iter()
+                        .map(move |reachable_position| (*component_position, *reachable_position))
+                })
+                .min_by_key(|(start, end)| {
+                    (start.distance_from(*end), start.x, start.y, end.x, end.y)
+                })
+                .unwrap();
+            // Either bend of the L-shaped corridor will do, as long as it doesn't cut through a vault
+            let corridor = vec![
+                corridor_positions(start, end),
+                corridor_positions(end, start),
+            ]
+            .into_iter()
+            .find(|corridor| {
+                corridor.iter().all(|position| {
+                    self.floor_positions.contains(position)
+                        || !self.vault_positions.contains(position)
+                })
+            })
+            .or_else(|| self.corridor_around_vaults(&component_positions, &reachable_positions))
+            .unwrap_or_else(|| corridor_positions(start, end));
+            self.floor_positions.extend(corridor);
+        }
+        self.wall_positions = walls_around(&self.floor_positions);
+    }
+
+    /// Breadth first search for the shortest winding corridor between the two areas that doesn't cut through any vault walls,
+    /// for when every L-shaped corridor would
+    fn corridor_around_vaults(
+        &self,
+        start_positions: &[Position],
+        end_positions: &HashSet<Position>,
+    ) -> Option<Vec<Position>> {
+        let (min_x, max_x, min_y, max_y) = self.floor_positions.union(&self.vault_positions).fold(
+            (i16::MAX, i16::MIN, i16::MAX, i16::MIN),
+            |(min_x, max_x, min_y, max_y), position| {
+                (
+                    min_x.min(position.x),
+                    max_x.max(position.x),
+                    min_y.min(position.y),
+                    max_y.max(position.y),
+                )
+            },
+        );
+        let is_passable = |position: &Position| {
+            (min_x - 1..=max_x + 1).contains(&position.x)
+                && (min_y - 1..=max_y + 1).contains(&position.y)
+                && (self.floor_positions.contains(position)
+                    || !self.vault_positions.contains(position))
+        };
+
+        let mut came_from = HashMap::new();
+        let mut frontier = VecDeque::new();
+        for start_position in start_positions {
+            came_from.insert(*start_position, None);
+            frontier.push_back(*start_position);
+        }
+        while let Some(visiting) = frontier.pop_front() {
+            if end_positions.contains(&visiting) {
+                let mut corridor = Vec::new();
+                let mut position = Some(visiting);
+                while let Some(current_position) = position {
+                    corridor.push(current_position);
+                    position = came_from[&current_position];
+                }
+                return Some(corridor);
+            }
+            for (x_offset, y_offset) in &[(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let next = Position::new(visiting.x + x_offset, visiting.y + y_offset);
+                if is_passable(&next) && !came_from.contains_key(&next) {
+                    came_from.insert(next, Some(visiting));
+                    frontier.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Generates floors from every generator with fixed seeds, stamps vaults into them and prints every connectivity failure,
+/// both before and after repairing and placing terrain
+/// Returns whether every floor was valid after repairing
+pub fn run_generator_test_harness(floors_per_generator: u64) -> bool {
+    let generators: Vec<Box<dyn DungeonGenerator>> = vec![
+        Box::new(RoomsGenerator),
+        Box::new(BspGenerator),
+        Box::new(CellularAutomataGenerator),
+        Box::new(DrunkardsWalkGenerator),
+    ];
+    let vaults = Vault::load_all("assets/vaults");
+
+    let mut all_floors_valid = true;
+    for generator in &generators {
+        let mut floors_needing_repair = 0;
+        let mut floors_failing_after_repair = 0;
+        for seed in 0..floors_per_generator {
+            let mut rng = Pcg64::seed_from_u64(seed);
+            let mut generated_floor = generator.generate(&mut rng);
+            generated_floor.stamp_random_vaults(&vaults, &mut rng);
+            if generated_floor.validate().is_err() {
+                floors_needing_repair += 1;
+            }
+            generated_floor.repair();
+            generated_floor.place_terrain(&mut rng);
+            if let Err(errors) = generated_floor.validate() {
+                floors_failing_after_repair += 1;
+                println!(
+                    "{} generator, seed {}: {:?}",
+                    generator.name(),
+                    seed,
+                    errors
+                );
+            }
+        }
+        println!(
+            "{} generator: {}/{} floors needed repairing, {} still failed after repairing",
+            generator.name(),
+            floors_needing_repair,
+            floors_per_generator,
+            floors_failing_after_repair
+        );
+        if floors_failing_after_repair != 0 {
+            all_floors_valid = false;
+        }
+    }
+    all_floors_valid
+}
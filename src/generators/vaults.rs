@@ -0,0 +1,198 @@
+use crate::data::{Position, Rarity};
+use crate::generators::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_pcg::Pcg64;
+use std::fs;
+
+const PLACEMENT_ATTEMPTS: u32 = 100;
+
+/// A hand made room stamped into generated floors, loaded from a text file
+/// Lines starting with ';' are comments, and the rest of the file is the layout:
+/// ' ' = Left as generated, '#' = Wall, '.' = Floor, 'S' = Staircase, 'P' = Spawner,
+/// 'c'/'u'/'r' = Common/Uncommon/Rare item, '1'/'2'/'3' = Common/Uncommon/Rare enemy,
+/// 'a' = Common enemy that's concealed until it's attacked, '+' = Door, 'x' = Secret wall,
+/// 'L' = Locked door, with a key placed somewhere on the floor outside of any vault, '^' = Random hidden trap
+#[derive(Debug, Clone)]
+pub struct Vault {
+    tiles: Vec<Vec<char>>,
+}
+
+impl Vault {
+    /// Loads every .txt file in the directory, in alphabetical order
+    pub fn load_all(directory: &str) -> Vec<Self> {
+        let mut paths = fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().and_then(|extension| extension.to_str()) == Some("txt"))
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+            .iter()
+            .map(|path| Self::parse(&fs::read_to_string(path).unwrap()))
+            .collect()
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let lines = text
+            .lines()
+            .filter(|line| !line.starts_with(';'))
+            .map(|line| line.trim_end().chars().collect::<Vec<char>>())
+            .collect::<Vec<Vec<char>>>();
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        let tiles = lines
+            .into_iter()
+            .map(|mut line| {
+                line.resize(width, ' ');
+                line
+            })
+            .collect();
+        Self { tiles }
+    }
+
+    /// Returns the layout rotated clockwise by 90 degrees a number of times, then optionally mirrored left to right
+    fn transformed(&self, rotations: u32, mirrored: bool) -> Vec<Vec<char>> {
+        let mut tiles = self.tiles.clone();
+        for _ in 0..rotations % 4 {
+            let height = tiles.len();
+            let width = tiles.first().map_or(0, |row| row.len());
+            tiles = (0..width)
+                .map(|column| (0..height).rev().map(|row| tiles[row][column]).collect())
+                .collect();
+        }
+        if mirrored {
+            for row in &mut tiles {
+                row.reverse();
+            }
+        }
+        tiles
+    }
+}
+
+impl GeneratedFloor {
+    /// Stamps up to 2 randomly chosen vaults into the floor
+    pub fn stamp_random_vaults(&mut self, vaults: &[Vault], rng: &mut Pcg64) {
+        for _ in 0..rng.gen_range(0, 3) {
+            if let Some(vault) = vaults.choose(rng) {
+                self.stamp_vault(vault, rng);
+            }
+        }
+    }
+
+    /// Stamps the vault with a random rotation and mirroring somewhere it doesn't touch the existing floor
+    /// Returns whether a place was found; the vault still needs to be connected with repair afterwards
+    pub fn stamp_vault(&mut self, vault: &Vault, rng: &mut Pcg64) -> bool {
+        let tiles = vault.transformed(rng.gen_range(0, 4), rng.gen());
+        let height = tiles.len() as i16;
+        let width = tiles.first().map_or(0, |row| row.len()) as i16;
+        let (min_x, max_x, min_y, max_y) = self.floor_positions.iter().fold(
+            (i16::MAX, i16::MIN, i16::MAX, i16::MIN),
+            |(min_x, max_x, min_y, max_y), position| {
+                (
+                    min_x.min(position.x),
+                    max_x.max(position.x),
+                    min_y.min(position.y),
+                    max_y.max(position.y),
+                )
+            },
+        );
+
+        'placement_loop: for _ in 0..PLACEMENT_ATTEMPTS {
+            // The top left corner of the vault
+            let corner = Position::new(
+                rng.gen_range(min_x - width, max_x + 2),
+                rng.gen_range(min_y, max_y + height + 2),
+            );
+            let vault_positions = tiles
+                .iter()
+                .enumerate()
+                .flat_map(|(row, line)| {
+                    line.iter().enumerate().map(move |(column, tile)| {
+                        (
+                            Position::new(corner.x + column as i16, corner.y - row as i16),
+                            *tile,
+                        )
+                    })
+                })
+                .filter(|(_, tile)| *tile != ' ')
+                .collect::<Vec<(Position, char)>>();
+            for (position, _) in &vault_positions {
+                if self.floor_positions.contains(position)
+                    || position
+                        .neighbors(&HashSet::new())
+                        .iter()
+                        .any(|neighbor| self.floor_positions.contains(neighbor))
+                {
+                    continue 'placement_loop;
+                }
+            }
+
+            let key_choices = {
+                let mut key_choices = self
+                    .floor_positions
+                    .iter()
+                    .filter(|position| {
+                        !self.vault_positions.contains(position)
+                            && **position != self.start_position
+                            && **position != self.staircase_position
+                            && !self.spawner_positions.contains(position)
+                            && !self
+                                .features
+                                .iter()
+                                .any(|(feature_position, _)| feature_position == *position)
+                    })
+                    .copied()
+                    .collect::<Vec<Position>>();
+                key_choices.sort_by_key(|position| (position.x, position.y));
+                key_choices
+            };
+            for (position, tile) in vault_positions {
+                self.vault_positions.insert(position);
+                if tile == '#' {
+                    continue;
+                }
+                self.floor_positions.insert(position);
+                match tile {
+                    'S' => self.staircase_position = position,
+                    'P' => self.spawner_positions.push(position),
+                    'c' => self
+                        .features
+                        .push((position, FloorFeature::Item(Rarity::Common))),
+                    'u' => self
+                        .features
+                        .push((position, FloorFeature::Item(Rarity::Uncommon))),
+                    'r' => self
+                        .features
+                        .push((position, FloorFeature::Item(Rarity::Rare))),
+                    '1' => self
+                        .features
+                        .push((position, FloorFeature::Enemy(Rarity::Common))),
+                    '2' => self
+                        .features
+                        .push((position, FloorFeature::Enemy(Rarity::Uncommon))),
+                    '3' => self
+                        .features
+                        .push((position, FloorFeature::Enemy(Rarity::Rare))),
+                    'a' => self
+                        .features
+                        .push((position, FloorFeature::ConcealedEnemy(Rarity::Common))),
+                    '+' => self.features.push((position, FloorFeature::Door)),
+                    '^' => self
+                        .features
+                        .push((position, FloorFeature::Trap(traps::random_trap_kind(rng)))),
+                    'x' => self.features.push((position, FloorFeature::SecretWall)),
+                    'L' => {
+                        self.features.push((position, FloorFeature::LockedDoor));
+                        if let Some(key_position) = key_choices.choose(rng) {
+                            self.features.push((*key_position, FloorFeature::Key));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            self.wall_positions = walls_around(&self.floor_positions);
+            return true;
+        }
+        false
+    }
+}
@@ -1,7 +1,9 @@
 use crate::attack::{damage, player_get_target, try_attack};
 use crate::data::*;
-use crate::items::create_random_scroll;
+use crate::items::{create_random_scroll, item_stats};
 use crate::movement::try_move;
+use crate::status_effects::apply_status;
+use crate::walls::damage_walls;
 use rand::seq::SliceRandom;
 use rand::Rng;
 use rand_distr::Triangular;
@@ -11,60 +13,98 @@ pub fn create_random_layer1(
     rarity: Rarity,
     world: &mut World,
 ) -> Option<fn(Option<Position>, &mut World) -> Entity> {
-    let rng = &mut world.fetch_mut::<RNG>().0;
-    let should_generate_item = match rarity {
-        Rarity::Common => rng.gen_ratio(1, 8),
-        Rarity::Uncommon => rng.gen_ratio(1, 4),
-        Rarity::Rare | Rarity::Epic => true,
+    let should_generate_item = {
+        let rng = &mut world.fetch_mut::<RNG>().0;
+        match rarity {
+            Rarity::Common => rng.gen_ratio(1, 8),
+            Rarity::Uncommon => rng.gen_ratio(1, 4),
+            Rarity::Rare | Rarity::Epic => true,
+        }
     };
     if should_generate_item {
-        let choices: Vec<fn(Option<Position>, &mut World) -> Entity> = match rarity {
-            Rarity::Common => vec![create_jump_saber, create_edge_of_ebony, create_blight_bow],
-            Rarity::Uncommon => vec![
-                create_improvised_spellbook,
-                create_daybreak,
-                create_random_scroll,
-            ],
-            Rarity::Rare => vec![create_twister_staff, create_netherbane],
-            Rarity::Epic => vec![],
-        };
-        return Some(*choices.choose(rng).unwrap());
+        Some(choose_random_layer1(rarity, world))
+    } else {
+        None
     }
-    None
+}
+
+/// Like create_random_layer1, but always picks an item
+pub fn choose_random_layer1(
+    rarity: Rarity,
+    world: &mut World,
+) -> fn(Option<Position>, &mut World) -> Entity {
+    let rng = &mut world.fetch_mut::<RNG>().0;
+    let choices: Vec<fn(Option<Position>, &mut World) -> Entity> = match rarity {
+        Rarity::Common => vec![create_jump_saber, create_edge_of_ebony, create_blight_bow],
+        Rarity::Uncommon => vec![
+            create_improvised_spellbook,
+            create_daybreak,
+            create_random_scroll,
+        ],
+        Rarity::Rare => vec![create_twister_staff, create_netherbane],
+        Rarity::Epic => vec![create_mothlight_lantern],
+    };
+    *choices.choose(rng).unwrap()
 }
 
 pub fn create_jump_saber(item_position: Option<Position>, world: &mut World) -> Entity {
     let mut e = world
         .create_entity()
         .with(Name::new("Jump Saber", false))
-        .with(Item::new(0, |_, world| {
-            let mut attack_succeeded = false;
-            if let Some(target_entity) = player_get_target(2, 2, world) {
-                let player_entity = {
-                    let (player_entity, player_facing_direction) = {
-                        let entities = world.entities();
-                        let player_data = world.read_storage::<Player>();
-                        let (player_entity, player) =
-                            (&entities, &player_data).join().next().unwrap();
-                        (player_entity, player.facing_direction)
+        .with(Item::new(
+            0,
+            "A saber that lets you leap at enemies one tile away",
+            ItemStats {
+                damage: Some((11, 11)),
+                targeting: Some(Targeting::new(2, 2, true, LineOfSight::FirstEntity)),
+                is_melee: true,
+                damage_type: DamageType::Physical,
+                critical_hit: Some((15, 2)),
+                special_effects: &["Jumps forward before attacking"],
+            },
+            |item_entity, world| {
+                let stats = item_stats(item_entity, world);
+                let mut attack_succeeded = false;
+                if let Some(target_entity) = player_get_target(stats.targeting.unwrap(), world) {
+                    let player_entity = {
+                        let (player_entity, player_facing_direction) = {
+                            let entities = world.entities();
+                            let player_data = world.read_storage::<Player>();
+                            let (player_entity, player) =
+                                (&entities, &player_data).join().next().unwrap();
+                            (player_entity, player.facing_direction)
+                        };
+                        if try_move(player_entity, player_facing_direction, world).is_ok() {
+                            Some(player_entity)
+                        } else {
+                            None
+                        }
                     };
-                    if try_move(player_entity, player_facing_direction, world).is_ok() {
-                        Some(player_entity)
-                    } else {
-                        None
+                    if let Some(player_entity) = player_entity {
+                        // The leap lands right next to the target
+                        attack_succeeded = try_attack(
+                            stats.damage.unwrap().0,
+                            stats.is_melee,
+                            stats.damage_type,
+                            stats.critical_hit,
+                            Targeting {
+                                minimum_range: 1,
+                                maximum_range: 1,
+                                ..stats.targeting.unwrap()
+                            },
+                            player_entity,
+                            target_entity,
+                            world,
+                        )
+                        .is_ok();
                     }
-                };
-                if let Some(player_entity) = player_entity {
-                    attack_succeeded =
-                        try_attack(11, true, false, 1, 1, player_entity, target_entity, world)
-                            .is_ok();
                 }
-            }
-            ItemResult {
-                should_end_turn: attack_succeeded,
-                should_consume_item: false,
-            }
-        }))
+                ItemResult {
+                    should_end_turn: attack_succeeded,
+                    should_consume_item: false,
+                }
+            },
+        ))
         .with(Sprite::new("jump_saber"));
     if let Some(item_position) = item_position {
         e = e.with(item_position);
@@ -76,34 +116,58 @@ pub fn create_edge_of_ebony(item_position: Option<Position>, world: &mut World)
     let mut e = world
         .create_entity()
         .with(Name::new("Edge of Ebony", false))
-        .with(Item::new(5, |_, world| {
-            if let Some(target_entity) = player_get_target(1, 1, world) {
-                let player_entity = {
-                    let entities = world.entities();
-                    let player_data = world.read_storage::<Player>();
-                    (&entities, &player_data).join().next().unwrap().0
-                };
-                let attack_result =
-                    try_attack(10, true, false, 1, 1, player_entity, target_entity, world);
-                if attack_result == Ok(false) {
-                    let rng = &mut world.fetch_mut::<RNG>().0;
-                    if rng.gen_ratio(1, 5) {
-                        let mut attackable_data = world.write_storage::<Attackable>();
-                        let target_attackable = attackable_data.get_mut(target_entity).unwrap();
-                        target_attackable.cant_attack_turns += 2;
+        .with(Item::new(
+            5,
+            "A blade of black glass that can stun whatever it cuts",
+            ItemStats {
+                damage: Some((10, 10)),
+                targeting: Some(Targeting::new(1, 1, true, LineOfSight::FirstEntity)),
+                is_melee: true,
+                damage_type: DamageType::Physical,
+                critical_hit: Some((25, 2)),
+                special_effects: &["1 in 5 chance the target can't attack for 2 turns"],
+            },
+            |item_entity, world| {
+                let stats = item_stats(item_entity, world);
+                if let Some(target_entity) = player_get_target(stats.targeting.unwrap(), world) {
+                    let player_entity = {
+                        let entities = world.entities();
+                        let player_data = world.read_storage::<Player>();
+                        (&entities, &player_data).join().next().unwrap().0
+                    };
+                    let attack_result = try_attack(
+                        stats.damage.unwrap().0,
+                        stats.is_melee,
+                        stats.damage_type,
+                        stats.critical_hit,
+                        stats.targeting.unwrap(),
+                        player_entity,
+                        target_entity,
+                        world,
+                    );
+                    if attack_result == Ok(AttackResult::Hit) {
+                        let should_disarm = world.fetch_mut::<RNG>().0.gen_ratio(1, 5);
+                        if should_disarm {
+                            apply_status(
+                                target_entity,
+                                StatusEffect::new(StatusKind::CantAttack, 1, Some(2)),
+                                Stacking::Add,
+                                world,
+                            );
+                        }
+                    }
+                    ItemResult {
+                        should_end_turn: attack_result.is_ok(),
+                        should_consume_item: false,
+                    }
+                } else {
+                    ItemResult {
+                        should_end_turn: false,
+                        should_consume_item: false,
                     }
                 }
-                ItemResult {
-                    should_end_turn: attack_result.is_ok(),
-                    should_consume_item: false,
-                }
-            } else {
-                ItemResult {
-                    should_end_turn: false,
-                    should_consume_item: false,
-                }
-            }
-        }))
+            },
+        ))
         .with(Sprite::new("edge_of_ebony"));
     if let Some(item_position) = item_position {
         e = e.with(item_position);
@@ -115,31 +179,56 @@ pub fn create_blight_bow(item_position: Option<Position>, world: &mut World) ->
     let mut e = world
         .create_entity()
         .with(Name::new("Blight Bow", false))
-        .with(Item::new(8, |_, world| {
-            if let Some(target_entity) = player_get_target(1, 2, world) {
-                let player_entity = {
-                    let entities = world.entities();
-                    let player_data = world.read_storage::<Player>();
-                    (&entities, &player_data).join().next().unwrap().0
-                };
-                let attack_result =
-                    try_attack(4, false, false, 1, 2, player_entity, target_entity, world);
-                if attack_result == Ok(false) {
-                    let mut attackable_data = world.write_storage::<Attackable>();
-                    let target_attackable = attackable_data.get_mut(target_entity).unwrap();
-                    target_attackable.blight_stacks += 6;
-                }
-                ItemResult {
-                    should_end_turn: attack_result.is_ok(),
-                    should_consume_item: false,
-                }
-            } else {
-                ItemResult {
-                    should_end_turn: false,
-                    should_consume_item: false,
+        .with(Item::new(
+            8,
+            "A bow that fires arrows coated in blight",
+            ItemStats {
+                damage: Some((4, 4)),
+                targeting: Some(Targeting::new(1, 2, true, LineOfSight::FirstEntity)),
+                is_melee: false,
+                damage_type: DamageType::Blight,
+                critical_hit: None,
+                special_effects: &["Applies 6 blight stacks"],
+            },
+            |item_entity, world| {
+                let stats = item_stats(item_entity, world);
+                if let Some(target_entity) = player_get_target(stats.targeting.unwrap(), world) {
+                    let player_entity = {
+                        let entities = world.entities();
+                        let player_data = world.read_storage::<Player>();
+                        (&entities, &player_data).join().next().unwrap().0
+                    };
+                    let attack_result = try_attack(
+                        stats.damage.unwrap().0,
+                        stats.is_melee,
+                        stats.damage_type,
+                        stats.critical_hit,
+                        stats.targeting.unwrap(),
+                        player_entity,
+                        target_entity,
+                        world,
+                    );
+                    if attack_result == Ok(AttackResult::Hit) {
+                        apply_status(
+                            target_entity,
+                            StatusEffect::new(StatusKind::Blight, 1, Some(6))
+                                .with_source(player_entity),
+                            Stacking::Add,
+                            world,
+                        );
+                    }
+                    ItemResult {
+                        should_end_turn: attack_result.is_ok(),
+                        should_consume_item: false,
+                    }
+                } else {
+                    ItemResult {
+                        should_end_turn: false,
+                        should_consume_item: false,
+                    }
                 }
-            }
-        }))
+            },
+        ))
         .with(Sprite::new("blight_bow"));
     if let Some(item_position) = item_position {
         e = e.with(item_position);
@@ -151,38 +240,59 @@ pub fn create_improvised_spellbook(item_position: Option<Position>, world: &mut
     let mut e = world
         .create_entity()
         .with(Name::new("Improvised Spellbook", false))
-        .with(Item::new(20, |_, world| {
-            if let Some(target_entity) = player_get_target(1, 3, world) {
-                let player_entity = {
-                    let entities = world.entities();
-                    let player_data = world.read_storage::<Player>();
-                    (&entities, &player_data).join().next().unwrap().0
-                };
-                let damage = {
-                    let rng = &mut world.fetch_mut::<RNG>().0;
-                    rng.sample(Triangular::new(0.0, 15.0, 8.0).unwrap()) as u32
-                };
-                let attack_result = try_attack(
-                    damage,
-                    false,
-                    true,
-                    1,
-                    3,
-                    player_entity,
-                    target_entity,
-                    world,
-                );
-                ItemResult {
-                    should_end_turn: attack_result.is_ok(),
-                    should_consume_item: false,
-                }
-            } else {
-                ItemResult {
-                    should_end_turn: false,
-                    should_consume_item: false,
+        .with(Item::new(
+            20,
+            "A hastily scribbled book of unreliable spells",
+            ItemStats {
+                damage: Some((0, 15)),
+                targeting: Some(Targeting::new(1, 3, true, LineOfSight::FirstEntity)),
+                is_melee: false,
+                damage_type: DamageType::Magic,
+                critical_hit: None,
+                special_effects: &[],
+            },
+            |item_entity, world| {
+                let stats = item_stats(item_entity, world);
+                if let Some(target_entity) = player_get_target(stats.targeting.unwrap(), world) {
+                    let player_entity = {
+                        let entities = world.entities();
+                        let player_data = world.read_storage::<Player>();
+                        (&entities, &player_data).join().next().unwrap().0
+                    };
+                    let damage = {
+                        let (minimum_damage, maximum_damage) = stats.damage.unwrap();
+                        let rng = &mut world.fetch_mut::<RNG>().0;
+                        rng.sample(
+                            Triangular::new(
+                                minimum_damage as f64,
+                                maximum_damage as f64,
+                                (minimum_damage + maximum_damage).div_ceil(2) as f64,
+                            )
+                            .unwrap(),
+                        ) as u32
+                    };
+                    let attack_result = try_attack(
+                        damage,
+                        stats.is_melee,
+                        stats.damage_type,
+                        stats.critical_hit,
+                        stats.targeting.unwrap(),
+                        player_entity,
+                        target_entity,
+                        world,
+                    );
+                    ItemResult {
+                        should_end_turn: attack_result.is_ok(),
+                        should_consume_item: false,
+                    }
+                } else {
+                    ItemResult {
+                        should_end_turn: false,
+                        should_consume_item: false,
+                    }
                 }
-            }
-        }))
+            },
+        ))
         .with(Sprite::new("improvised_spellbook"));
     if let Some(item_position) = item_position {
         e = e.with(item_position);
@@ -194,36 +304,55 @@ pub fn create_daybreak(item_position: Option<Position>, world: &mut World) -> En
     let mut e = world
         .create_entity()
         .with(Name::new("Daybreak", false))
-        .with(Item::new(15, |_, world| {
-            let mut should_end_turn = false;
-            for range in &[1, 2] {
-                if let Some(target_entity) = player_get_target(*range, *range, world) {
-                    let player_entity = {
-                        let entities = world.entities();
-                        let player_data = world.read_storage::<Player>();
-                        (&entities, &player_data).join().next().unwrap().0
+        .with(Item::new(
+            15,
+            "A greatsword long enough to cut through two enemies at once",
+            ItemStats {
+                damage: Some((12, 12)),
+                targeting: Some(Targeting::new(1, 2, false, LineOfSight::Piercing)),
+                is_melee: true,
+                damage_type: DamageType::Physical,
+                critical_hit: None,
+                special_effects: &["Hits at both range 1 and range 2"],
+            },
+            |item_entity, world| {
+                let stats = item_stats(item_entity, world);
+                let targeting = stats.targeting.unwrap();
+                let mut should_end_turn = false;
+                for range in targeting.minimum_range..=targeting.maximum_range {
+                    let range_targeting = Targeting {
+                        minimum_range: range,
+                        maximum_range: range,
+                        ..targeting
                     };
-                    if try_attack(
-                        12,
-                        true,
-                        false,
-                        *range,
-                        *range,
-                        player_entity,
-                        target_entity,
-                        world,
-                    )
-                    .is_ok()
-                    {
-                        should_end_turn = true;
+                    if let Some(target_entity) = player_get_target(range_targeting, world) {
+                        let player_entity = {
+                            let entities = world.entities();
+                            let player_data = world.read_storage::<Player>();
+                            (&entities, &player_data).join().next().unwrap().0
+                        };
+                        if try_attack(
+                            stats.damage.unwrap().0,
+                            stats.is_melee,
+                            stats.damage_type,
+                            stats.critical_hit,
+                            range_targeting,
+                            player_entity,
+                            target_entity,
+                            world,
+                        )
+                        .is_ok()
+                        {
+                            should_end_turn = true;
+                        }
                     }
                 }
-            }
-            ItemResult {
-                should_end_turn,
-                should_consume_item: false,
-            }
-        }))
+                ItemResult {
+                    should_end_turn,
+                    should_consume_item: false,
+                }
+            },
+        ))
         .with(Sprite::new("daybreak"));
     if let Some(item_position) = item_position {
         e = e.with(item_position);
@@ -235,35 +364,73 @@ pub fn create_twister_staff(item_position: Option<Position>, world: &mut World)
     let mut e = world
         .create_entity()
         .with(Name::new("Twister Staff", false))
-        .with(Item::new(10, |_, world| {
-            if let Some(target_entity) = player_get_target(1, 2, world) {
-                let player_entity = {
-                    let entities = world.entities();
-                    let player_data = world.read_storage::<Player>();
-                    (&entities, &player_data).join().next().unwrap().0
-                };
-                let attack_result =
-                    try_attack(8, false, true, 1, 2, player_entity, target_entity, world);
-                if let Ok(false) = attack_result {
-                    let player_facing_direction = {
+        .with(Item::new(
+            10,
+            "A staff that blasts enemies back with a gust of wind",
+            ItemStats {
+                damage: Some((8, 8)),
+                targeting: Some(Targeting::new(1, 2, false, LineOfSight::FirstEntity)),
+                is_melee: false,
+                damage_type: DamageType::Magic,
+                critical_hit: None,
+                special_effects: &[
+                    "Knocks the target back 1 tile",
+                    "Deals 2 extra damage if the target can't be knocked back",
+                    "Damages the wall the target is knocked into",
+                ],
+            },
+            |item_entity, world| {
+                let stats = item_stats(item_entity, world);
+                if let Some(target_entity) = player_get_target(stats.targeting.unwrap(), world) {
+                    let player_entity = {
+                        let entities = world.entities();
                         let player_data = world.read_storage::<Player>();
-                        player_data.get(player_entity).unwrap().facing_direction
+                        (&entities, &player_data).join().next().unwrap().0
                     };
-                    if try_move(target_entity, player_facing_direction, world).is_err() {
-                        damage(2, false, false, Some(player_entity), target_entity, world);
+                    let attack_result = try_attack(
+                        stats.damage.unwrap().0,
+                        stats.is_melee,
+                        stats.damage_type,
+                        stats.critical_hit,
+                        stats.targeting.unwrap(),
+                        player_entity,
+                        target_entity,
+                        world,
+                    );
+                    if let Ok(AttackResult::Hit) = attack_result {
+                        let player_facing_direction = {
+                            let player_data = world.read_storage::<Player>();
+                            player_data.get(player_entity).unwrap().facing_direction
+                        };
+                        if try_move(target_entity, player_facing_direction, world).is_err() {
+                            let impact_position = world
+                                .read_storage::<Position>()
+                                .get(target_entity)
+                                .unwrap()
+                                .offset_by(player_facing_direction);
+                            damage(
+                                2,
+                                false,
+                                DamageType::Physical,
+                                Some(player_entity),
+                                target_entity,
+                                world,
+                            );
+                            damage_walls(&[impact_position], 4, world);
+                        }
+                    }
+                    ItemResult {
+                        should_end_turn: attack_result.is_ok(),
+                        should_consume_item: false,
+                    }
+                } else {
+                    ItemResult {
+                        should_end_turn: false,
+                        should_consume_item: false,
                     }
                 }
-                ItemResult {
-                    should_end_turn: attack_result.is_ok(),
-                    should_consume_item: false,
-                }
-            } else {
-                ItemResult {
-                    should_end_turn: false,
-                    should_consume_item: false,
-                }
-            }
-        }))
+            },
+        ))
         .with(Sprite::new("twister_staff"));
     if let Some(item_position) = item_position {
         e = e.with(item_position);
@@ -275,47 +442,116 @@ pub fn create_netherbane(item_position: Option<Position>, world: &mut World) ->
     let mut e = world
         .create_entity()
         .with(Name::new("Netherbane", false))
-        .with(Item::new(0, |item_entity, world| {
-            if let Some(target_entity) = player_get_target(1, 1, world) {
-                let (player_entity, damage) = {
-                    let entities = world.entities();
-                    let player_data = world.read_storage::<Player>();
-                    let counter_data = world.read_storage::<Counter>();
-                    (
-                        (&entities, &player_data).join().next().unwrap().0,
-                        counter_data.get(item_entity).unwrap().0,
-                    )
-                };
-                let attack_result = try_attack(
-                    damage,
-                    true,
-                    false,
-                    1,
-                    1,
-                    player_entity,
-                    target_entity,
-                    world,
-                );
-                if attack_result == Ok(true) {
-                    let mut counter_data = world.write_storage::<Counter>();
-                    let item_damage_counter = counter_data.get_mut(item_entity).unwrap();
-                    item_damage_counter.0 += 1;
-                }
-                ItemResult {
-                    should_end_turn: attack_result.is_ok(),
-                    should_consume_item: false,
-                }
-            } else {
-                ItemResult {
-                    should_end_turn: false,
-                    should_consume_item: false,
+        .with(Item::new(
+            0,
+            "A cursed blade that feeds on the souls of its victims",
+            ItemStats {
+                damage: Some((3, 3)),
+                targeting: Some(Targeting::new(1, 1, true, LineOfSight::FirstEntity)),
+                is_melee: true,
+                damage_type: DamageType::Physical,
+                critical_hit: Some((10, 3)),
+                special_effects: &["Gains 1 damage for every enemy it kills"],
+            },
+            |item_entity, world| {
+                let stats = item_stats(item_entity, world);
+                if let Some(target_entity) = player_get_target(stats.targeting.unwrap(), world) {
+                    let player_entity = {
+                        let entities = world.entities();
+                        let player_data = world.read_storage::<Player>();
+                        (&entities, &player_data).join().next().unwrap().0
+                    };
+                    let attack_result = try_attack(
+                        stats.damage.unwrap().0,
+                        stats.is_melee,
+                        stats.damage_type,
+                        stats.critical_hit,
+                        stats.targeting.unwrap(),
+                        player_entity,
+                        target_entity,
+                        world,
+                    );
+                    if attack_result == Ok(AttackResult::Killed) {
+                        let mut item_data = world.write_storage::<Item>();
+                        let item = item_data.get_mut(item_entity).unwrap();
+                        let damage = item.stats.damage.unwrap().0 + 1;
+                        item.stats.damage = Some((damage, damage));
+                    }
+                    ItemResult {
+                        should_end_turn: attack_result.is_ok(),
+                        should_consume_item: false,
+                    }
+                } else {
+                    ItemResult {
+                        should_end_turn: false,
+                        should_consume_item: false,
+                    }
                 }
-            }
-        }))
-        .with(Counter(3))
+            },
+        ))
         .with(Sprite::new("netherbane"));
     if let Some(item_position) = item_position {
         e = e.with(item_position);
     }
     e.build()
 }
+
+pub fn create_mothlight_lantern(item_position: Option<Position>, world: &mut World) -> Entity {
+    let mut e = world
+        .create_entity()
+        .with(Name::new("Mothlight Lantern", false))
+        .with(Item::new(
+            20,
+            "The Moth Priestess's lantern, burning with a light too bright to look at",
+            ItemStats {
+                damage: Some((14, 14)),
+                targeting: Some(Targeting::new(1, 4, true, LineOfSight::FirstEntity)),
+                is_melee: false,
+                damage_type: DamageType::Magic,
+                critical_hit: None,
+                special_effects: &["The target can't attack for 3 turns"],
+            },
+            |item_entity, world| {
+                let stats = item_stats(item_entity, world);
+                if let Some(target_entity) = player_get_target(stats.targeting.unwrap(), world) {
+                    let player_entity = {
+                        let entities = world.entities();
+                        let player_data = world.read_storage::<Player>();
+                        (&entities, &player_data).join().next().unwrap().0
+                    };
+                    let attack_result = try_attack(
+                        stats.damage.unwrap().0,
+                        stats.is_melee,
+                        stats.damage_type,
+                        stats.critical_hit,
+                        stats.targeting.unwrap(),
+                        player_entity,
+                        target_entity,
+                        world,
+                    );
+                    if attack_result == Ok(AttackResult::Hit) {
+                        apply_status(
+                            target_entity,
+                            StatusEffect::new(StatusKind::CantAttack, 1, Some(3)),
+                            Stacking::Add,
+                            world,
+                        );
+                    }
+                    ItemResult {
+                        should_end_turn: attack_result.is_ok(),
+                        should_consume_item: false,
+                    }
+                } else {
+                    ItemResult {
+                        should_end_turn: false,
+                        should_consume_item: false,
+                    }
+                }
+            },
+        ))
+        .with(Sprite::new("mothlight_lantern"));
+    if let Some(item_position) = item_position {
+        e = e.with(item_position);
+    }
+    e.build()
+}
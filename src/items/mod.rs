@@ -4,31 +4,87 @@ pub use layer1::*;
 
 use crate::attack::{damage, player_get_target, try_attack};
 use crate::data::*;
+use crate::fire::ignite;
+use crate::gas::release_gas;
+use crate::movement::obstacle_positions;
 use rand::seq::SliceRandom;
 use specs::{Builder, Entities, Entity, Join, ReadStorage, World, WorldExt};
 use std::collections::HashSet;
 
+/// Looks up how to create any item from its name, used when loading map files
+pub fn create_function_by_name(name: &str) -> Option<fn(Option<Position>, &mut World) -> Entity> {
+    let create_function: fn(Option<Position>, &mut World) -> Entity = match name {
+        "Makeshift Dagger" => create_makeshift_dagger,
+        "Scroll of Shadows" => create_scroll_of_shadows,
+        "Scroll of Displacement" => create_scroll_of_displacement,
+        "Scroll of Entanglement" => create_scroll_of_entanglement,
+        "Scroll of Lightning" => create_scroll_of_lightning,
+        "Scroll of Fire" => create_scroll_of_fire,
+        "Scroll of Miasma" => create_scroll_of_miasma,
+        "Jump Saber" => create_jump_saber,
+        "Edge of Ebony" => create_edge_of_ebony,
+        "Blight Bow" => create_blight_bow,
+        "Improvised Spellbook" => create_improvised_spellbook,
+        "Daybreak" => create_daybreak,
+        "Twister Staff" => create_twister_staff,
+        "Netherbane" => create_netherbane,
+        "Mothlight Lantern" => create_mothlight_lantern,
+        "Vault Key" => create_vault_key,
+        _ => return None,
+    };
+    Some(create_function)
+}
+
+/// Items read their stats back when used, so what's shown in the bag is always what's used in combat
+fn item_stats(item_entity: Entity, world: &World) -> ItemStats {
+    world.read_storage::<Item>().get(item_entity).unwrap().stats
+}
+
 pub fn create_makeshift_dagger(item_position: Option<Position>, world: &mut World) -> Entity {
     let mut e = world
         .create_entity()
         .with(Name::new("Makeshift Dagger", false))
-        .with(Item::new(0, |_, world| {
-            let mut attack_succeeded = false;
-            if let Some(target_entity) = player_get_target(1, 1, world) {
-                let player_entity = {
-                    let entities = world.entities();
-                    let player_data = world.read_storage::<Player>();
-                    (&entities, &player_data).join().next().unwrap().0
-                };
-                if try_attack(8, true, false, 1, 1, player_entity, target_entity, world).is_ok() {
-                    attack_succeeded = true;
+        .with(Item::new(
+            0,
+            "A crude blade, better than nothing",
+            ItemStats {
+                damage: Some((8, 8)),
+                targeting: Some(Targeting::new(1, 1, true, LineOfSight::FirstEntity)),
+                is_melee: true,
+                damage_type: DamageType::Physical,
+                critical_hit: Some((10, 2)),
+                special_effects: &[],
+            },
+            |item_entity, world| {
+                let stats = item_stats(item_entity, world);
+                let mut attack_succeeded = false;
+                if let Some(target_entity) = player_get_target(stats.targeting.unwrap(), world) {
+                    let player_entity = {
+                        let entities = world.entities();
+                        let player_data = world.read_storage::<Player>();
+                        (&entities, &player_data).join().next().unwrap().0
+                    };
+                    if try_attack(
+                        stats.damage.unwrap().0,
+                        stats.is_melee,
+                        stats.damage_type,
+                        stats.critical_hit,
+                        stats.targeting.unwrap(),
+                        player_entity,
+                        target_entity,
+                        world,
+                    )
+                    .is_ok()
+                    {
+                        attack_succeeded = true;
+                    }
                 }
-            }
-            ItemResult {
-                should_end_turn: attack_succeeded,
-                should_consume_item: false,
-            }
-        }))
+                ItemResult {
+                    should_end_turn: attack_succeeded,
+                    should_consume_item: false,
+                }
+            },
+        ))
         .with(Sprite::new("makeshift_dagger"));
     if let Some(item_position) = item_position {
         e = e.with(item_position);
@@ -52,6 +108,7 @@ pub fn create_random_good_scroll(item_position: Option<Position>, world: &mut Wo
             create_scroll_of_displacement,
             create_scroll_of_entanglement,
             create_scroll_of_lightning,
+            create_scroll_of_fire,
         ];
         *choices.choose(rng).unwrap()
     };
@@ -61,53 +118,157 @@ pub fn create_random_good_scroll(item_position: Option<Position>, world: &mut Wo
 pub fn create_bad_scroll(item_position: Option<Position>, world: &mut World) -> Entity {
     let create_function = {
         let rng = &mut world.fetch_mut::<RNG>().0;
-        let choices = [create_scroll_of_shadows];
+        let choices = [create_scroll_of_shadows, create_scroll_of_miasma];
         *choices.choose(rng).unwrap()
     };
     (create_function)(item_position, world)
 }
 
+pub fn create_vault_key(item_position: Option<Position>, world: &mut World) -> Entity {
+    let mut e = world
+        .create_entity()
+        .with(Name::new("Vault Key", false))
+        .with(Item::new(
+            0,
+            "A heavy key that opens a locked door somewhere on this floor",
+            ItemStats {
+                damage: None,
+                targeting: None,
+                is_melee: false,
+                damage_type: DamageType::Physical,
+                critical_hit: None,
+                special_effects: &["Used up when you interact with a locked door"],
+            },
+            |_, world| {
+                let mut message_log = world.fetch_mut::<MessageLog>();
+                message_log.new_message(
+                    "Face a locked door and interact with it to use the key",
+                    MessageColor::White,
+                    MessageDisplayLength::Medium,
+                );
+                ItemResult {
+                    should_end_turn: false,
+                    should_consume_item: false,
+                }
+            },
+        ))
+        .with(Key {})
+        .with(Sprite::new("vault_key"));
+    if let Some(item_position) = item_position {
+        e = e.with(item_position);
+    }
+    e.build()
+}
+
 pub fn create_scroll_of_shadows(item_position: Option<Position>, world: &mut World) -> Entity {
     let sprite = world.fetch::<ScrollInfo>().scroll_of_shadows_sprite;
     let concealed = !world.fetch::<ScrollInfo>().scroll_of_shadows_identified;
     let mut e = world
         .create_entity()
         .with(Name::new("Scroll of Shadows", concealed))
-        .with(Item::new(0, |_, world| {
-            let mut message_log = world.fetch_mut::<MessageLog>();
-            message_log.new_message(
-                "You used a Scroll of Shadows!",
-                MessageColor::White,
-                MessageDisplayLength::Medium,
-            );
-
-            world.fetch_mut::<ScrollInfo>().scroll_of_shadows_identified = true;
-            let mut player_data = world.write_storage::<Player>();
-            let mut name_data = world.write_storage::<Name>();
-            let player = (&mut player_data).join().next().unwrap();
-            for item_entity in player.inventory.iter().flatten() {
-                let item_name = name_data.get_mut(*item_entity).unwrap();
-                if item_name.text == "Scroll of Shadows" {
-                    item_name.concealed = false;
+        .with(Item::new(
+            0,
+            "A scroll that hides the true form of every enemy on the floor",
+            ItemStats {
+                damage: None,
+                targeting: None,
+                is_melee: false,
+                damage_type: DamageType::Physical,
+                critical_hit: None,
+                special_effects: &["Conceals all enemies, including ones spawned later"],
+            },
+            |_, world| {
+                let mut message_log = world.fetch_mut::<MessageLog>();
+                message_log.new_message(
+                    "You used a Scroll of Shadows!",
+                    MessageColor::White,
+                    MessageDisplayLength::Medium,
+                );
+
+                world.fetch_mut::<ScrollInfo>().scroll_of_shadows_identified = true;
+                let mut player_data = world.write_storage::<Player>();
+                let mut name_data = world.write_storage::<Name>();
+                let player = (&mut player_data).join().next().unwrap();
+                for item_entity in player.inventory.iter().flatten() {
+                    let item_name = name_data.get_mut(*item_entity).unwrap();
+                    if item_name.text == "Scroll of Shadows" {
+                        item_name.concealed = false;
+                    }
                 }
-            }
-
-            let mut spawner_data = world.write_storage::<Spawner>();
-            for spawner in (&mut spawner_data).join() {
-                spawner.spawn_concealed = true;
-            }
-
-            let mut sprite_data = world.write_storage::<Sprite>();
-            let attackable_data = world.read_storage::<Attackable>();
-            for (sprite, _, _) in (&mut sprite_data, &attackable_data, !&player_data).join() {
-                sprite.id = "concealed";
-            }
-
-            ItemResult {
-                should_end_turn: true,
-                should_consume_item: true,
-            }
-        }))
+
+                let mut spawner_data = world.write_storage::<Spawner>();
+                for spawner in (&mut spawner_data).join() {
+                    spawner.spawn_concealed = true;
+                }
+
+                let mut sprite_data = world.write_storage::<Sprite>();
+                let attackable_data = world.read_storage::<Attackable>();
+                for (sprite, _, _) in (&mut sprite_data, &attackable_data, !&player_data).join() {
+                    sprite.id = "concealed";
+                }
+
+                ItemResult {
+                    should_end_turn: true,
+                    should_consume_item: true,
+                }
+            },
+        ))
+        .with(Sprite::new(sprite));
+    if let Some(item_position) = item_position {
+        e = e.with(item_position);
+    }
+    e.build()
+}
+
+pub fn create_scroll_of_miasma(item_position: Option<Position>, world: &mut World) -> Entity {
+    let sprite = world.fetch::<ScrollInfo>().scroll_of_miasma_sprite;
+    let concealed = !world.fetch::<ScrollInfo>().scroll_of_miasma_identified;
+    let mut e = world
+        .create_entity()
+        .with(Name::new("Scroll of Miasma", concealed))
+        .with(Item::new(
+            0,
+            "A scroll that bursts into a cloud of poison gas around its reader",
+            ItemStats {
+                damage: None,
+                targeting: None,
+                is_melee: false,
+                damage_type: DamageType::Physical,
+                critical_hit: None,
+                special_effects: &["Releases poison gas on you, which spreads to nearby tiles"],
+            },
+            |_, world| {
+                let player_position = {
+                    let mut message_log = world.fetch_mut::<MessageLog>();
+                    message_log.new_message(
+                        "You used a Scroll of Miasma!",
+                        MessageColor::White,
+                        MessageDisplayLength::Medium,
+                    );
+
+                    world.fetch_mut::<ScrollInfo>().scroll_of_miasma_identified = true;
+                    let mut player_data = world.write_storage::<Player>();
+                    let mut name_data = world.write_storage::<Name>();
+                    let position_data = world.read_storage::<Position>();
+                    let (player, player_position) =
+                        (&mut player_data, &position_data).join().next().unwrap();
+                    for item_entity in player.inventory.iter().flatten() {
+                        let item_name = name_data.get_mut(*item_entity).unwrap();
+                        if item_name.text == "Scroll of Miasma" {
+                            item_name.concealed = false;
+                        }
+                    }
+                    *player_position
+                };
+
+                release_gas(GasKind::Poison, player_position, 200, world);
+
+                ItemResult {
+                    should_end_turn: true,
+                    should_consume_item: true,
+                }
+            },
+        ))
         .with(Sprite::new(sprite));
     if let Some(item_position) = item_position {
         e = e.with(item_position);
@@ -123,55 +284,66 @@ pub fn create_scroll_of_displacement(item_position: Option<Position>, world: &mu
     let mut e = world
         .create_entity()
         .with(Name::new("Scroll of Displacement", concealed))
-        .with(Item::new(0, |_, world| {
-            let mut message_log = world.fetch_mut::<MessageLog>();
-            message_log.new_message(
-                "You used a Scroll of Displacement!",
-                MessageColor::White,
-                MessageDisplayLength::Medium,
-            );
-
-            world
-                .fetch_mut::<ScrollInfo>()
-                .scroll_of_displacement_identified = true;
-            let mut player_data = world.write_storage::<Player>();
-            let mut name_data = world.write_storage::<Name>();
-            let player = (&mut player_data).join().next().unwrap();
-            for item_entity in player.inventory.iter().flatten() {
-                let item_name = name_data.get_mut(*item_entity).unwrap();
-                if item_name.text == "Scroll of Displacement" {
-                    item_name.concealed = false;
+        .with(Item::new(
+            0,
+            "A scroll that whisks you away to somewhere else on the floor",
+            ItemStats {
+                damage: None,
+                targeting: None,
+                is_melee: false,
+                damage_type: DamageType::Physical,
+                critical_hit: None,
+                special_effects: &["Teleports you to a random open position"],
+            },
+            |_, world| {
+                let mut message_log = world.fetch_mut::<MessageLog>();
+                message_log.new_message(
+                    "You used a Scroll of Displacement!",
+                    MessageColor::White,
+                    MessageDisplayLength::Medium,
+                );
+
+                world
+                    .fetch_mut::<ScrollInfo>()
+                    .scroll_of_displacement_identified = true;
+                let mut player_data = world.write_storage::<Player>();
+                let mut name_data = world.write_storage::<Name>();
+                let player = (&mut player_data).join().next().unwrap();
+                for item_entity in player.inventory.iter().flatten() {
+                    let item_name = name_data.get_mut(*item_entity).unwrap();
+                    if item_name.text == "Scroll of Displacement" {
+                        item_name.concealed = false;
+                    }
                 }
-            }
-
-            let mut new_player_position = None;
-            let mut position_data = world.write_storage::<Position>();
-            let intangible_data = world.read_storage::<Intangible>();
-            let mut rng = world.fetch_mut::<RNG>();
-            let mut possible_new_positions = (&position_data, &intangible_data)
-                .join()
-                .map(|(position, _)| position)
-                .collect::<Vec<&Position>>();
-            possible_new_positions.shuffle(&mut rng.0);
-            for position1 in possible_new_positions {
-                if !(&position_data, !&intangible_data)
+
+                let mut new_player_position = None;
+                let obstacles = obstacle_positions(world);
+                let mut position_data = world.write_storage::<Position>();
+                let intangible_data = world.read_storage::<Intangible>();
+                let mut rng = world.fetch_mut::<RNG>();
+                let mut possible_new_positions = (&position_data, &intangible_data)
                     .join()
-                    .any(|(position2, _)| position1 == position2)
-                {
-                    new_player_position = Some(*position1);
-                    break;
+                    .map(|(position, _)| position)
+                    .collect::<Vec<&Position>>();
+                possible_new_positions.shuffle(&mut rng.0);
+                for position in possible_new_positions {
+                    if !obstacles.contains(position) {
+                        new_player_position = Some(*position);
+                        break;
+                    }
+                }
+                if let Some(new_player_position) = new_player_position {
+                    let player_position =
+                        (&player_data, &mut position_data).join().next().unwrap().1;
+                    *player_position = new_player_position;
+                }
+
+                ItemResult {
+                    should_end_turn: true,
+                    should_consume_item: true,
                 }
-            }
-            if let Some(new_player_position) = new_player_position {
-                let player_position = (&player_data, &mut position_data).join().next().unwrap().1;
-                *player_position = new_player_position;
-            }
-
-            ItemResult {
-                should_end_turn: true,
-                should_consume_item: true,
-            }
-        }))
+            },
+        ))
         .with(Sprite::new(sprite));
     if let Some(item_position) = item_position {
         e = e.with(item_position);
@@ -187,46 +359,73 @@ pub fn create_scroll_of_entanglement(item_position: Option<Position>, world: &mu
     let mut e = world
         .create_entity()
         .with(Name::new("Scroll of Entanglement", concealed))
-        .with(Item::new(0, |_, world| {
-            let mut message_log = world.fetch_mut::<MessageLog>();
-            message_log.new_message(
-                "You used a Scroll of Entanglement!",
-                MessageColor::White,
-                MessageDisplayLength::Medium,
-            );
-
-            world
-                .fetch_mut::<ScrollInfo>()
-                .scroll_of_entanglement_identified = true;
-            let mut player_data = world.write_storage::<Player>();
-            let mut name_data = world.write_storage::<Name>();
-            let player = (&mut player_data).join().next().unwrap();
-            for item_entity in player.inventory.iter().flatten() {
-                let item_name = name_data.get_mut(*item_entity).unwrap();
-                if item_name.text == "Scroll of Entanglement" {
-                    item_name.concealed = false;
+        .with(Item::new(
+            0,
+            "A scroll that binds nearby enemies with roots",
+            ItemStats {
+                damage: None,
+                targeting: None,
+                is_melee: false,
+                damage_type: DamageType::Physical,
+                critical_hit: None,
+                special_effects: &[
+                    "Enemies within 5 tiles can't attack for 8 turns",
+                    "Enemies within 5 tiles can't move for 16 turns",
+                ],
+            },
+            |_, world| {
+                let mut message_log = world.fetch_mut::<MessageLog>();
+                message_log.new_message(
+                    "You used a Scroll of Entanglement!",
+                    MessageColor::White,
+                    MessageDisplayLength::Medium,
+                );
+
+                world
+                    .fetch_mut::<ScrollInfo>()
+                    .scroll_of_entanglement_identified = true;
+                let mut player_data = world.write_storage::<Player>();
+                let mut name_data = world.write_storage::<Name>();
+                let player = (&mut player_data).join().next().unwrap();
+                for item_entity in player.inventory.iter().flatten() {
+                    let item_name = name_data.get_mut(*item_entity).unwrap();
+                    if item_name.text == "Scroll of Entanglement" {
+                        item_name.concealed = false;
+                    }
                 }
-            }
-
-            let mut attackable_data = world.write_storage::<Attackable>();
-            let position_data = world.read_storage::<Position>();
-            let player_position = (&player_data, &position_data).join().next().unwrap().1;
-            for (entity_attackable, entity_position, _) in
-                (&mut attackable_data, &position_data, !&player_data).join()
-            {
-                if (player_position.x - entity_position.x).abs() <= 5
-                    && (player_position.y - entity_position.y).abs() <= 5
+
+                let entities = world.entities();
+                let attackable_data = world.read_storage::<Attackable>();
+                let position_data = world.read_storage::<Position>();
+                let mut status_effects_data = world.write_storage::<StatusEffects>();
+                let player_position = (&player_data, &position_data).join().next().unwrap().1;
+                for (entity, _, entity_position, _) in
+                    (&entities, &attackable_data, &position_data, !&player_data).join()
                 {
-                    entity_attackable.cant_attack_turns += 8;
-                    entity_attackable.cant_move_turns += 16;
+                    if (player_position.x - entity_position.x).abs() <= 5
+                        && (player_position.y - entity_position.y).abs() <= 5
+                    {
+                        let entity_status_effects = status_effects_data
+                            .entry(entity)
+                            .unwrap()
+                            .or_insert_with(StatusEffects::default);
+                        entity_status_effects.apply(
+                            StatusEffect::new(StatusKind::CantAttack, 1, Some(8)),
+                            Stacking::Add,
+                        );
+                        entity_status_effects.apply(
+                            StatusEffect::new(StatusKind::CantMove, 1, Some(16)),
+                            Stacking::Add,
+                        );
+                    }
                 }
-            }
 
-            ItemResult {
-                should_end_turn: true,
-                should_consume_item: true,
-            }
-        }))
+                ItemResult {
+                    should_end_turn: true,
+                    should_consume_item: true,
+                }
+            },
+        ))
         .with(Sprite::new(sprite));
     if let Some(item_position) = item_position {
         e = e.with(item_position);
@@ -240,90 +439,197 @@ pub fn create_scroll_of_lightning(item_position: Option<Position>, world: &mut W
     let mut e = world
         .create_entity()
         .with(Name::new("Scroll of Lightning", concealed))
-        .with(Item::new(0, |_, world| {
-            {
-                let mut message_log = world.fetch_mut::<MessageLog>();
-                message_log.new_message(
-                    "You used a Scroll of Lightning!",
-                    MessageColor::White,
-                    MessageDisplayLength::Medium,
-                );
+        .with(Item::new(
+            0,
+            "A scroll that calls down lightning that arcs between enemies",
+            ItemStats {
+                damage: Some((9, 9)),
+                targeting: None,
+                is_melee: false,
+                damage_type: DamageType::Lightning,
+                critical_hit: None,
+                special_effects: &["Chains between enemies within 3 tiles of each other"],
+            },
+            |item_entity, world| {
+                let stats = item_stats(item_entity, world);
+                {
+                    let mut message_log = world.fetch_mut::<MessageLog>();
+                    message_log.new_message(
+                        "You used a Scroll of Lightning!",
+                        MessageColor::White,
+                        MessageDisplayLength::Medium,
+                    );
 
-                world
-                    .fetch_mut::<ScrollInfo>()
-                    .scroll_of_lightning_identified = true;
-                let mut player_data = world.write_storage::<Player>();
-                let mut name_data = world.write_storage::<Name>();
-                let player = (&mut player_data).join().next().unwrap();
-                for item_entity in player.inventory.iter().flatten() {
-                    let item_name = name_data.get_mut(*item_entity).unwrap();
-                    if item_name.text == "Scroll of Lightning" {
-                        item_name.concealed = false;
+                    world
+                        .fetch_mut::<ScrollInfo>()
+                        .scroll_of_lightning_identified = true;
+                    let mut player_data = world.write_storage::<Player>();
+                    let mut name_data = world.write_storage::<Name>();
+                    let player = (&mut player_data).join().next().unwrap();
+                    for item_entity in player.inventory.iter().flatten() {
+                        let item_name = name_data.get_mut(*item_entity).unwrap();
+                        if item_name.text == "Scroll of Lightning" {
+                            item_name.concealed = false;
+                        }
                     }
                 }
-            }
-
-            fn add_targets(
-                entity: Entity,
-                seen_entities: &mut HashSet<Entity>,
-                player_position: Position,
-                entities: &Entities,
-                attackable_data: &ReadStorage<Attackable>,
-                position_data: &ReadStorage<Position>,
-            ) {
-                let entity_position = position_data.get(entity).unwrap();
-                for (next_entity, next_entity_position, _) in
-                    (entities, position_data, attackable_data).join()
-                {
-                    if (entity_position.x - next_entity_position.x).abs() <= 3
-                        && (entity_position.y - next_entity_position.y).abs() <= 3
-                        && next_entity_position != &player_position
-                        && !seen_entities.contains(&next_entity)
+
+                fn add_targets(
+                    entity: Entity,
+                    seen_entities: &mut HashSet<Entity>,
+                    player_position: Position,
+                    entities: &Entities,
+                    attackable_data: &ReadStorage<Attackable>,
+                    position_data: &ReadStorage<Position>,
+                ) {
+                    let entity_position = position_data.get(entity).unwrap();
+                    for (next_entity, next_entity_position, _) in
+                        (entities, position_data, attackable_data).join()
                     {
-                        seen_entities.insert(next_entity);
-                        add_targets(
-                            next_entity,
-                            seen_entities,
-                            player_position,
-                            entities,
-                            attackable_data,
-                            position_data,
+                        if (entity_position.x - next_entity_position.x).abs() <= 3
+                            && (entity_position.y - next_entity_position.y).abs() <= 3
+                            && next_entity_position != &player_position
+                            && !seen_entities.contains(&next_entity)
+                        {
+                            seen_entities.insert(next_entity);
+                            add_targets(
+                                next_entity,
+                                seen_entities,
+                                player_position,
+                                entities,
+                                attackable_data,
+                                position_data,
+                            );
+                        }
+                    }
+                };
+                let mut targets;
+                {
+                    let mut seen_entities = HashSet::new();
+                    let entities = world.entities();
+                    let attackable_data = world.read_storage::<Attackable>();
+                    let position_data = world.read_storage::<Position>();
+                    let player_data = world.read_storage::<Player>();
+                    let mut rng = world.fetch_mut::<RNG>();
+                    let (player_entity, _, player_position) =
+                        (&entities, &player_data, &position_data)
+                            .join()
+                            .next()
+                            .unwrap();
+                    add_targets(
+                        player_entity,
+                        &mut seen_entities,
+                        *player_position,
+                        &entities,
+                        &attackable_data,
+                        &position_data,
+                    );
+                    targets = seen_entities.into_iter().collect::<Vec<Entity>>();
+                    targets.shuffle(&mut rng.0);
+                }
+                for entity in targets {
+                    // An earlier strike could have blown it up along with something else
+                    if !world.is_alive(entity) {
+                        continue;
+                    }
+                    let entity_name = *world.read_storage::<Name>().get(entity).unwrap();
+                    let (_, damage_dealt, resistance) = damage(
+                        stats.damage.unwrap().0,
+                        stats.is_melee,
+                        stats.damage_type,
+                        None,
+                        entity,
+                        world,
+                    );
+                    world.fetch_mut::<MessageLog>().new_message(
+                        format!(
+                            "{} was struck by lightning for {} damage{}",
+                            entity_name.get_text(),
+                            damage_dealt,
+                            resistance.map_or("", Resistance::describe),
+                        ),
+                        MessageColor::White,
+                        MessageDisplayLength::Short,
+                    );
+                }
+
+                ItemResult {
+                    should_end_turn: true,
+                    should_consume_item: true,
+                }
+            },
+        ))
+        .with(Sprite::new(sprite));
+    if let Some(item_position) = item_position {
+        e = e.with(item_position);
+    }
+    e.build()
+}
+
+pub fn create_scroll_of_fire(item_position: Option<Position>, world: &mut World) -> Entity {
+    let sprite = world.fetch::<ScrollInfo>().scroll_of_fire_sprite;
+    let concealed = !world.fetch::<ScrollInfo>().scroll_of_fire_identified;
+    let mut e = world
+        .create_entity()
+        .with(Name::new("Scroll of Fire", concealed))
+        .with(Item::new(
+            0,
+            "A scroll that sets the ground around you alight",
+            ItemStats {
+                damage: None,
+                targeting: None,
+                is_melee: false,
+                damage_type: DamageType::Fire,
+                critical_hit: None,
+                special_effects: &[
+                    "Lights a ring of fire 2 tiles around you",
+                    "Fire spreads across moss",
+                ],
+            },
+            |_, world| {
+                let player_position = {
+                    let mut message_log = world.fetch_mut::<MessageLog>();
+                    message_log.new_message(
+                        "You used a Scroll of Fire!",
+                        MessageColor::White,
+                        MessageDisplayLength::Medium,
+                    );
+
+                    world.fetch_mut::<ScrollInfo>().scroll_of_fire_identified = true;
+                    let mut player_data = world.write_storage::<Player>();
+                    let mut name_data = world.write_storage::<Name>();
+                    let position_data = world.read_storage::<Position>();
+                    let (player, player_position) =
+                        (&mut player_data, &position_data).join().next().unwrap();
+                    for item_entity in player.inventory.iter().flatten() {
+                        let item_name = name_data.get_mut(*item_entity).unwrap();
+                        if item_name.text == "Scroll of Fire" {
+                            item_name.concealed = false;
+                        }
+                    }
+                    *player_position
+                };
+
+                let mut ring_positions = Vec::new();
+                for x_offset in -2..=2 {
+                    for y_offset in -2..=2 {
+                        let position = Position::new(
+                            player_position.x + x_offset,
+                            player_position.y + y_offset,
                         );
+                        if position.chebyshev_distance_from(player_position) == 2 {
+                            ring_positions.push(position);
+                        }
                     }
                 }
-            };
-            let mut targets;
-            {
-                let mut seen_entities = HashSet::new();
-                let entities = world.entities();
-                let attackable_data = world.read_storage::<Attackable>();
-                let position_data = world.read_storage::<Position>();
-                let player_data = world.read_storage::<Player>();
-                let mut rng = world.fetch_mut::<RNG>();
-                let (player_entity, _, player_position) = (&entities, &player_data, &position_data)
-                    .join()
-                    .next()
-                    .unwrap();
-                add_targets(
-                    player_entity,
-                    &mut seen_entities,
-                    *player_position,
-                    &entities,
-                    &attackable_data,
-                    &position_data,
-                );
-                targets = seen_entities.into_iter().collect::<Vec<Entity>>();
-                targets.shuffle(&mut rng.0);
-            }
-            for entity in targets {
-                damage(9, false, true, None, entity, world);
-            }
-
-            ItemResult {
-                should_end_turn: true,
-                should_consume_item: true,
-            }
-        }))
+                ignite(&ring_positions, world);
+
+                ItemResult {
+                    should_end_turn: true,
+                    should_consume_item: true,
+                }
+            },
+        ))
         .with(Sprite::new(sprite));
     if let Some(item_position) = item_position {
         e = e.with(item_position);
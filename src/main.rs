@@ -1,24 +1,42 @@
+mod arenas;
 mod attack;
 mod data;
+mod doors;
 mod drain_crystals;
 mod end_of_turn;
 mod enemy_controller;
 mod entities;
+mod examine;
+mod fire;
+mod gas;
 mod generate_dungeon;
+mod generators;
 mod items;
+mod map_file;
 mod movement;
 mod player_controller;
 mod render;
 mod spawn;
+mod status_effects;
+mod telegraphs;
+mod temporary;
+mod terrain;
+mod traps;
+mod triggers;
+mod walls;
 
+use attack::player_get_targets;
 use data::*;
 use drain_crystals::drain_crystals_system;
 use end_of_turn::end_of_turn;
 use enemy_controller::enemy_controller_system;
 use generate_dungeon::GenerateDungeonSystem;
-use player_controller::{PlayerActed, PlayerAction, PlayerControllerSystem};
+use map_file::{export_floor, import_floor, MAP_FILE_PATH};
+use player_controller::{item_targeting, PlayerActed, PlayerAction, PlayerControllerSystem};
 use render::RenderSystem;
 use spawn::tick_spawners;
+use telegraphs::tick_telegraphs;
+use temporary::tick_temporaries;
 
 use sdl2::event::Event;
 use sdl2::keyboard::Scancode;
@@ -26,21 +44,55 @@ use specs::{Join, World, WorldExt};
 use std::time::{Duration, Instant};
 
 fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+    if args.get(1).map(String::as_str) == Some("--test-generators") {
+        let floors_per_generator = args
+            .get(2)
+            .and_then(|floors| floors.parse().ok())
+            .unwrap_or(100);
+        if !generators::run_generator_test_harness(floors_per_generator) {
+            std::process::exit(1);
+        }
+        return;
+    }
+    let map_path = args
+        .iter()
+        .position(|arg| arg == "--load-map")
+        .and_then(|index| args.get(index + 1))
+        .cloned();
+
     let sdl_context = sdl2::init().unwrap();
     let mut event_pump = sdl_context.event_pump().unwrap();
 
     let mut world = World::new();
     world.register::<Name>();
     world.register::<Position>();
+    world.register::<Footprint>();
     world.register::<Sprite>();
     world.register::<Attackable>();
     world.register::<AI>();
+    world.register::<CachedPath>();
     world.register::<Counter>();
     world.register::<Intangible>();
     world.register::<Player>();
     world.register::<Staircase>();
     world.register::<Spawner>();
     world.register::<Item>();
+    world.register::<Telegraph>();
+    world.register::<Temporary>();
+    world.register::<MovementDelay>();
+    world.register::<Door>();
+    world.register::<SecretWall>();
+    world.register::<OpensDoors>();
+    world.register::<IgnoresStench>();
+    world.register::<Key>();
+    world.register::<Trap>();
+    world.register::<Terrain>();
+    world.register::<Fire>();
+    world.register::<Gas>();
+    world.register::<Breakable>();
+    world.register::<StatusEffects>();
+    world.register::<Triggers>();
     world.insert(GameState::NewGame);
     world.insert(MessageLog::new());
     let mut rng = RNG::new();
@@ -129,27 +181,184 @@ fn main() {
                     last_input_time = Instant::now();
                     player_controller_system.action = PlayerAction::Interact;
                 }
+                let mut item_slot_pressed = None;
                 if keyboard.is_scancode_pressed(Scancode::Num1) {
-                    last_input_time = Instant::now();
-                    player_controller_system.action = PlayerAction::UseItem(ItemSlot::One);
+                    item_slot_pressed = Some(ItemSlot::One);
                 }
                 if keyboard.is_scancode_pressed(Scancode::Num2) {
-                    last_input_time = Instant::now();
-                    player_controller_system.action = PlayerAction::UseItem(ItemSlot::Two);
+                    item_slot_pressed = Some(ItemSlot::Two);
                 }
                 if keyboard.is_scancode_pressed(Scancode::Num3) {
-                    last_input_time = Instant::now();
-                    player_controller_system.action = PlayerAction::UseItem(ItemSlot::Three);
+                    item_slot_pressed = Some(ItemSlot::Three);
                 }
                 if keyboard.is_scancode_pressed(Scancode::Num4) {
+                    item_slot_pressed = Some(ItemSlot::Four);
+                }
+                if let Some(item_slot) = item_slot_pressed {
                     last_input_time = Instant::now();
-                    player_controller_system.action = PlayerAction::UseItem(ItemSlot::Four);
+                    player_controller_system.action = PlayerAction::UseItem(item_slot);
+                    if let Some(targeting) = item_targeting(item_slot, &world) {
+                        if targeting.maximum_range > 1 {
+                            player_controller_system.action = PlayerAction::None;
+                            if player_get_targets(targeting, &world).is_empty() {
+                                world.fetch_mut::<MessageLog>().new_message(
+                                    "There's nothing in range to target",
+                                    MessageColor::White,
+                                    MessageDisplayLength::Short,
+                                );
+                            } else {
+                                *world.fetch_mut::<GameState>() =
+                                    GameState::Targeting(item_slot, 0);
+                            }
+                        }
+                    }
                 }
                 if keyboard.is_scancode_pressed(Scancode::Space) {
                     last_input_time = Instant::now();
                     player_controller_system.action = PlayerAction::None;
                     *world.fetch_mut::<GameState>() = GameState::BagUI(BagUIState::Overview(0, 0));
                 }
+                if keyboard.is_scancode_pressed(Scancode::L) {
+                    last_input_time = Instant::now();
+                    player_controller_system.action = PlayerAction::None;
+                    *world.fetch_mut::<GameState>() = GameState::Examine(7, 7);
+                }
+                if keyboard.is_scancode_pressed(Scancode::F5) {
+                    last_input_time = Instant::now();
+                    player_controller_system.action = PlayerAction::None;
+                    let message = match std::fs::write(MAP_FILE_PATH, export_floor(&world)) {
+                        Ok(()) => (
+                            format!("Saved the floor to {}", MAP_FILE_PATH),
+                            MessageColor::White,
+                        ),
+                        Err(error) => (
+                            format!("Couldn't save the floor: {}", error),
+                            MessageColor::Red,
+                        ),
+                    };
+                    world.fetch_mut::<MessageLog>().new_message(
+                        message.0,
+                        message.1,
+                        MessageDisplayLength::Medium,
+                    );
+                }
+                if keyboard.is_scancode_pressed(Scancode::F9) {
+                    last_input_time = Instant::now();
+                    player_controller_system.action = PlayerAction::None;
+                    load_map(MAP_FILE_PATH, &mut world);
+                }
+            }
+
+            if let GameState::Targeting(item_slot, selected_target_index) = game_state {
+                if last_input_time.elapsed() >= Duration::from_millis(150) {
+                    let targets = match item_targeting(item_slot, &world) {
+                        Some(targeting) => player_get_targets(targeting, &world),
+                        None => Vec::new(),
+                    };
+                    let mut keystate = 0;
+                    if keyboard.is_scancode_pressed(Scancode::W)
+                        || keyboard.is_scancode_pressed(Scancode::Up)
+                        || keyboard.is_scancode_pressed(Scancode::A)
+                        || keyboard.is_scancode_pressed(Scancode::Left)
+                    {
+                        last_input_time = Instant::now();
+                        keystate = -1;
+                    }
+                    if keyboard.is_scancode_pressed(Scancode::S)
+                        || keyboard.is_scancode_pressed(Scancode::Down)
+                        || keyboard.is_scancode_pressed(Scancode::D)
+                        || keyboard.is_scancode_pressed(Scancode::Right)
+                    {
+                        last_input_time = Instant::now();
+                        keystate = 1;
+                    }
+                    if targets.is_empty() {
+                        *world.fetch_mut::<GameState>() = GameState::PlayerTurn;
+                    } else {
+                        let selected_target_index = (selected_target_index as i32 + keystate)
+                            .rem_euclid(targets.len() as i32)
+                            as usize;
+                        *world.fetch_mut::<GameState>() =
+                            GameState::Targeting(item_slot, selected_target_index);
+
+                        if keyboard.is_scancode_pressed(Scancode::Return) {
+                            last_input_time = Instant::now();
+                            let target_entity = targets[selected_target_index];
+                            {
+                                let mut player_data = world.write_storage::<Player>();
+                                let position_data = world.read_storage::<Position>();
+                                let footprint_data = world.read_storage::<Footprint>();
+                                let (player, player_position) =
+                                    (&mut player_data, &position_data).join().next().unwrap();
+                                // Face the closest part of big targets, the same tile targeting measures to
+                                let target_position = position_data
+                                    .get(target_entity)
+                                    .unwrap()
+                                    .footprint_positions(footprint_data.get(target_entity))
+                                    .into_iter()
+                                    .min_by_key(|position| {
+                                        position.chebyshev_distance_from(*player_position)
+                                    })
+                                    .unwrap();
+                                if let Some(direction) = Direction::from_offset(
+                                    (target_position.x - player_position.x).signum(),
+                                    (target_position.y - player_position.y).signum(),
+                                ) {
+                                    player.facing_direction = direction;
+                                }
+                                player.selected_target = Some(target_entity);
+                            }
+                            player_controller_system.action = PlayerAction::UseItem(item_slot);
+                            *world.fetch_mut::<GameState>() = GameState::PlayerTurn;
+                        }
+                    }
+
+                    if keyboard.is_scancode_pressed(Scancode::Space) {
+                        last_input_time = Instant::now();
+                        *world.fetch_mut::<GameState>() = GameState::PlayerTurn;
+                    }
+                }
+            }
+
+            if let GameState::Examine(cursor_x, cursor_y) = game_state {
+                if last_input_time.elapsed() >= Duration::from_millis(150) {
+                    let mut keystate = (0, 0);
+                    if keyboard.is_scancode_pressed(Scancode::W)
+                        || keyboard.is_scancode_pressed(Scancode::Up)
+                    {
+                        last_input_time = Instant::now();
+                        keystate.1 = -1;
+                    }
+                    if keyboard.is_scancode_pressed(Scancode::A)
+                        || keyboard.is_scancode_pressed(Scancode::Left)
+                    {
+                        last_input_time = Instant::now();
+                        keystate.0 = -1;
+                    }
+                    if keyboard.is_scancode_pressed(Scancode::S)
+                        || keyboard.is_scancode_pressed(Scancode::Down)
+                    {
+                        last_input_time = Instant::now();
+                        keystate.1 = 1;
+                    }
+                    if keyboard.is_scancode_pressed(Scancode::D)
+                        || keyboard.is_scancode_pressed(Scancode::Right)
+                    {
+                        last_input_time = Instant::now();
+                        keystate.0 = 1;
+                    }
+                    *world.fetch_mut::<GameState>() = GameState::Examine(
+                        (cursor_x + keystate.0).clamp(0, 14),
+                        (cursor_y + keystate.1).clamp(0, 14),
+                    );
+
+                    if keyboard.is_scancode_pressed(Scancode::L)
+                        || keyboard.is_scancode_pressed(Scancode::Space)
+                    {
+                        last_input_time = Instant::now();
+                        *world.fetch_mut::<GameState>() = GameState::PlayerTurn;
+                    }
+                }
             }
 
             if let GameState::BagUI(bag_ui_state) = game_state {
@@ -361,6 +570,9 @@ fn main() {
                     world.insert(RNG::new());
                     entities::create_player(&mut world);
                     generate_dungeon_system.run(&mut world);
+                    if let Some(map_path) = &map_path {
+                        load_map(map_path, &mut world);
+                    }
                     tick_spawners(&mut world);
                 }
                 GameState::PlayerTurn => {
@@ -373,6 +585,8 @@ fn main() {
                 }
                 GameState::EnemyTurn => {
                     tick_spawners(&mut world);
+                    tick_telegraphs(&mut world);
+                    tick_temporaries(&mut world);
                     enemy_controller_system(&mut world);
                     end_of_turn(&mut world);
                     world.insert(GameState::PlayerTurn);
@@ -384,3 +598,23 @@ fn main() {
         render_system.run(&mut world);
     }
 }
+
+/// Replaces the current floor with the one in the map file, logging why if it couldn't be loaded
+fn load_map(path: &str, world: &mut World) {
+    let result = std::fs::read_to_string(path)
+        .map_err(|error| error.to_string())
+        .and_then(|text| import_floor(&text, world));
+    let message = match result {
+        Ok(()) => (
+            format!("Loaded the floor from {}", path),
+            MessageColor::White,
+        ),
+        Err(error) => (
+            format!("Couldn't load {}: {}", path, error),
+            MessageColor::Red,
+        ),
+    };
+    world
+        .fetch_mut::<MessageLog>()
+        .new_message(message.0, message.1, MessageDisplayLength::Medium);
+}
@@ -0,0 +1,452 @@
+use crate::data::*;
+use crate::doors::open_door;
+use crate::entities::{self, CreateFunction, MapEntityKind};
+use crate::generate_dungeon::delete_floor;
+use crate::items;
+use crate::traps::reveal_trap;
+use specs::{Entity, Join, World, WorldExt};
+use std::collections::HashMap;
+
+/// Where floors are saved to and loaded from in game
+pub const MAP_FILE_PATH: &str = "floor.txt";
+
+// A map file looks like:
+// player <x> <y> <facing direction> <health>
+// tiles <left x> <top y> <width> <height>
+// <one line of tiles per row, starting from the top: '#' = Wall, '%' = Mossy wall, '.' = Floor,
+//  '~' = Shallow water, ':' = Chasm, '"' = Toxic moss, ',' = Rubble, ' ' = Nothing>
+// entity <x> <y> <name>[; health <current health>][; concealed][; open][; unlocked][; revealed]
+//  [; durability <durability>][; temporary <turns left>][; turns_per_spawn <turns>]
+// item <x> <y> <name>
+// Lines starting with ';' are comments
+
+/// Writes the current floor and the player's position on it as text
+pub fn export_floor(world: &World) -> String {
+    let entities = world.entities();
+    let name_data = world.read_storage::<Name>();
+    let position_data = world.read_storage::<Position>();
+    let sprite_data = world.read_storage::<Sprite>();
+    let attackable_data = world.read_storage::<Attackable>();
+    let player_data = world.read_storage::<Player>();
+    let item_data = world.read_storage::<Item>();
+    let telegraph_data = world.read_storage::<Telegraph>();
+    let secret_wall_data = world.read_storage::<SecretWall>();
+    let door_data = world.read_storage::<Door>();
+    let trap_data = world.read_storage::<Trap>();
+    let terrain_data = world.read_storage::<Terrain>();
+    let gas_data = world.read_storage::<Gas>();
+    let breakable_data = world.read_storage::<Breakable>();
+    let temporary_data = world.read_storage::<Temporary>();
+    let spawner_data = world.read_storage::<Spawner>();
+
+    let mut lines = vec!["; Exported floor".to_owned()];
+    let (player, player_position, player_attackable) =
+        (&player_data, &position_data, &attackable_data)
+            .join()
+            .next()
+            .unwrap();
+    lines.push(format!(
+        "player {} {} {:?} {}",
+        player_position.x,
+        player_position.y,
+        player.facing_direction,
+        player_attackable.current_health
+    ));
+
+    let mut tiles = HashMap::new();
+    let mut entity_lines = Vec::new();
+    for (entity, name, position, sprite) in
+        (&entities, &name_data, &position_data, sprite_data.maybe()).join()
+    {
+        if player_data.contains(entity)
+            || telegraph_data.contains(entity)
+            || gas_data.contains(entity)
+        {
+            continue;
+        }
+        // Walls only need their own line if they've been damaged or will crumble on their own
+        let is_damaged_or_temporary = breakable_data
+            .get(entity)
+            .filter(|breakable| breakable.durability != breakable.max_durability)
+            .is_some()
+            || temporary_data.contains(entity);
+        match name.text {
+            "Floor" => {
+                tiles.entry(*position).or_insert('.');
+            }
+            _ if terrain_data.contains(entity) => {
+                let tile = match terrain_data.get(entity).unwrap().kind {
+                    TerrainKind::ShallowWater => '~',
+                    TerrainKind::Chasm => ':',
+                    TerrainKind::ToxicMoss => '"',
+                    TerrainKind::Rubble => ',',
+                };
+                tiles.insert(*position, tile);
+            }
+            _ if secret_wall_data.contains(entity) => {
+                entity_lines.push(format!("entity {} {} Secret Wall", position.x, position.y));
+            }
+            "Wall" if !attackable_data.contains(entity) && !is_damaged_or_temporary => {
+                let is_mossy = sprite.map(|sprite| sprite.id) == Some("wall_mossy");
+                tiles.insert(*position, if is_mossy { '%' } else { '#' });
+            }
+            _ if item_data.contains(entity) => {
+                entity_lines.push(format!("item {} {} {}", position.x, position.y, name.text));
+            }
+            _ => {
+                let map_name = if name.text == "Wall"
+                    && sprite.map(|sprite| sprite.id) == Some("wall_mossy")
+                {
+                    "Mossy Wall"
+                } else {
+                    name.text
+                };
+                let mut line = format!("entity {} {} {}", position.x, position.y, map_name);
+                if let Some(attackable) = attackable_data.get(entity) {
+                    if attackable.current_health != attackable.max_health {
+                        line.push_str(&format!("; health {}", attackable.current_health));
+                    }
+                }
+                if sprite.map(|sprite| sprite.id) == Some("concealed") {
+                    line.push_str("; concealed");
+                }
+                if let Some(door) = door_data.get(entity) {
+                    if door.is_open {
+                        line.push_str("; open");
+                    } else if name.text == "Locked Door" && !door.is_locked {
+                        line.push_str("; unlocked");
+                    }
+                }
+                if trap_data.get(entity).map(|trap| trap.is_hidden) == Some(false) {
+                    line.push_str("; revealed");
+                }
+                if let Some(breakable) = breakable_data.get(entity) {
+                    if breakable.durability != breakable.max_durability {
+                        line.push_str(&format!("; durability {}", breakable.durability));
+                    }
+                }
+                if let Some(temporary) = temporary_data.get(entity) {
+                    line.push_str(&format!("; temporary {}", temporary.turns_left));
+                }
+                if let Some(spawner) = spawner_data.get(entity) {
+                    if spawner.turns_per_spawn != Spawner::new().turns_per_spawn {
+                        line.push_str(&format!("; turns_per_spawn {}", spawner.turns_per_spawn));
+                    }
+                }
+                entity_lines.push(line);
+            }
+        }
+    }
+
+    if !tiles.is_empty() {
+        let min_x = tiles.keys().map(|position| position.x).min().unwrap();
+        let max_x = tiles.keys().map(|position| position.x).max().unwrap();
+        let min_y = tiles.keys().map(|position| position.y).min().unwrap();
+        let max_y = tiles.keys().map(|position| position.y).max().unwrap();
+        lines.push(format!(
+            "tiles {} {} {} {}",
+            min_x,
+            max_y,
+            max_x - min_x + 1,
+            max_y - min_y + 1
+        ));
+        for y in (min_y..=max_y).rev() {
+            let row = (min_x..=max_x)
+                .map(|x| *tiles.get(&Position::new(x, y)).unwrap_or(&' '))
+                .collect::<String>();
+            lines.push(row.trim_end().to_owned());
+        }
+    }
+
+    entity_lines.sort();
+    lines.append(&mut entity_lines);
+    lines.join("\n") + "\n"
+}
+
+/// A line of a map file, parsed before anything on the current floor is touched
+enum MapLine {
+    Player {
+        position: Position,
+        facing_direction: Direction,
+        current_health: u32,
+    },
+    // Tiles are created the same way as entities, just without attributes
+    Entity {
+        create_function: CreateFunction,
+        position: Position,
+        attributes: Vec<EntityAttribute>,
+    },
+    Item {
+        create_function: fn(Option<Position>, &mut World) -> Entity,
+        position: Position,
+    },
+}
+
+enum EntityAttribute {
+    Health(u32),
+    Concealed,
+    Revealed,
+    Open,
+    Unlocked,
+    Durability(u32),
+    Temporary(u32),
+    TurnsPerSpawn(u32),
+}
+
+/// Replaces the current floor with the one in the text, keeping the player's inventory
+pub fn import_floor(text: &str, world: &mut World) -> Result<(), String> {
+    let map_lines = parse_floor(text)?;
+
+    delete_floor(world);
+    world.remove::<BossArena>();
+
+    for map_line in map_lines {
+        match map_line {
+            MapLine::Player {
+                position,
+                facing_direction,
+                current_health,
+            } => {
+                let mut player_data = world.write_storage::<Player>();
+                let mut position_data = world.write_storage::<Position>();
+                let mut attackable_data = world.write_storage::<Attackable>();
+                let (player, player_position, player_attackable) =
+                    (&mut player_data, &mut position_data, &mut attackable_data)
+                        .join()
+                        .next()
+                        .unwrap();
+                *player_position = position;
+                player.facing_direction = facing_direction;
+                player_attackable.current_health = current_health.min(player_attackable.max_health);
+            }
+            MapLine::Entity {
+                create_function,
+                position,
+                attributes,
+            } => {
+                let entity = create_function(position, world);
+                for attribute in attributes {
+                    match attribute {
+                        EntityAttribute::Health(health) => {
+                            let mut attackable_data = world.write_storage::<Attackable>();
+                            let attackable = attackable_data.get_mut(entity).unwrap();
+                            attackable.current_health = health.min(attackable.max_health);
+                        }
+                        EntityAttribute::Concealed => {
+                            let mut sprite_data = world.write_storage::<Sprite>();
+                            if let Some(sprite) = sprite_data.get_mut(entity) {
+                                sprite.id = "concealed";
+                            }
+                        }
+                        EntityAttribute::Revealed => reveal_trap(entity, world),
+                        EntityAttribute::Open => open_door(entity, world),
+                        EntityAttribute::Unlocked => {
+                            world
+                                .write_storage::<Door>()
+                                .get_mut(entity)
+                                .unwrap()
+                                .is_locked = false;
+                            world
+                                .write_storage::<Sprite>()
+                                .insert(entity, Sprite::new("door_closed"))
+                                .unwrap();
+                        }
+                        EntityAttribute::Durability(durability) => {
+                            let mut breakable_data = world.write_storage::<Breakable>();
+                            let breakable = breakable_data.get_mut(entity).unwrap();
+                            breakable.durability = durability.min(breakable.max_durability);
+                        }
+                        EntityAttribute::Temporary(turns_left) => {
+                            world
+                                .write_storage::<Temporary>()
+                                .insert(entity, Temporary::new(turns_left))
+                                .unwrap();
+                        }
+                        EntityAttribute::TurnsPerSpawn(turns_per_spawn) => {
+                            world
+                                .write_storage::<Spawner>()
+                                .get_mut(entity)
+                                .unwrap()
+                                .turns_per_spawn = turns_per_spawn;
+                        }
+                    }
+                }
+            }
+            MapLine::Item {
+                create_function,
+                position,
+            } => {
+                create_function(Some(position), world);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses and checks the whole map file, so a bad line is reported before the current floor is deleted
+fn parse_floor(text: &str) -> Result<Vec<MapLine>, String> {
+    let mut map_lines = Vec::new();
+    let mut lines = text.lines().enumerate();
+    while let Some((line_index, line)) = lines.next() {
+        let line_error = |message: &str| format!("Line {}: {}", line_index + 1, message);
+        if line.trim().is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        let mut attributes = line.split("; ");
+        let mut words = attributes.next().unwrap_or_default().split_whitespace();
+        let keyword = words
+            .next()
+            .ok_or_else(|| line_error("Expected a keyword"))?;
+        let mut next_number = || {
+            words
+                .next()
+                .and_then(|word| word.parse::<i16>().ok())
+                .ok_or_else(|| line_error("Expected a number"))
+        };
+        match keyword {
+            "player" => {
+                let position = Position::new(next_number()?, next_number()?);
+                let facing_direction = match words.next() {
+                    Some("Up") => Direction::Up,
+                    Some("Down") => Direction::Down,
+                    Some("Left") => Direction::Left,
+                    Some("Right") => Direction::Right,
+                    Some("UpLeft") => Direction::UpLeft,
+                    Some("DownLeft") => Direction::DownLeft,
+                    Some("DownRight") => Direction::DownRight,
+                    Some("UpRight") => Direction::UpRight,
+                    _ => return Err(line_error("Expected a facing direction")),
+                };
+                let current_health = words
+                    .next()
+                    .and_then(|word| word.parse::<u32>().ok())
+                    .ok_or_else(|| line_error("Expected the player's health"))?;
+                map_lines.push(MapLine::Player {
+                    position,
+                    facing_direction,
+                    current_health,
+                });
+            }
+            "tiles" => {
+                let (left_x, top_y) = (next_number()?, next_number()?);
+                let (width, height) = (next_number()?, next_number()?);
+                for row in 0..height {
+                    let (_, tile_line) = lines
+                        .next()
+                        .ok_or_else(|| line_error("Expected more rows of tiles"))?;
+                    for (column, tile) in tile_line.chars().take(width as usize).enumerate() {
+                        let create_function: CreateFunction = match tile {
+                            '.' => entities::create_floor,
+                            '#' => |position, world| {
+                                entities::create_wall_with_sprite(position, "wall", world)
+                            },
+                            '%' => |position, world| {
+                                entities::create_wall_with_sprite(position, "wall_mossy", world)
+                            },
+                            '~' => |position, world| {
+                                entities::create_terrain(TerrainKind::ShallowWater, position, world)
+                            },
+                            ':' => |position, world| {
+                                entities::create_terrain(TerrainKind::Chasm, position, world)
+                            },
+                            '"' => |position, world| {
+                                entities::create_terrain(TerrainKind::ToxicMoss, position, world)
+                            },
+                            ',' => |position, world| {
+                                entities::create_terrain(TerrainKind::Rubble, position, world)
+                            },
+                            ' ' => continue,
+                            _ => return Err(line_error(&format!("Unknown tile '{}'", tile))),
+                        };
+                        map_lines.push(MapLine::Entity {
+                            create_function,
+                            position: Position::new(left_x + column as i16, top_y - row),
+                            attributes: Vec::new(),
+                        });
+                    }
+                }
+            }
+            "entity" | "item" => {
+                let position = Position::new(next_number()?, next_number()?);
+                let name = words.collect::<Vec<&str>>().join(" ");
+                if keyword == "item" {
+                    let create_function = items::create_function_by_name(&name)
+                        .ok_or_else(|| line_error(&format!("Unknown item \"{}\"", name)))?;
+                    map_lines.push(MapLine::Item {
+                        create_function,
+                        position,
+                    });
+                    continue;
+                }
+
+                let (create_function, kind) = entities::create_function_by_name(&name)
+                    .ok_or_else(|| line_error(&format!("Unknown entity \"{}\"", name)))?;
+                let attribute_number = |word: &str, attribute_name: &str| {
+                    word.parse::<u32>().map_err(|_| {
+                        line_error(&format!("Expected a number for {}", attribute_name))
+                    })
+                };
+                let mut entity_attributes = Vec::new();
+                for attribute in attributes {
+                    let mut attribute_words = attribute.split_whitespace();
+                    let entity_attribute = match (attribute_words.next(), attribute_words.next()) {
+                        (Some("health"), Some(health)) => {
+                            EntityAttribute::Health(attribute_number(health, "health")?)
+                        }
+                        (Some("concealed"), None) => EntityAttribute::Concealed,
+                        (Some("revealed"), None) => EntityAttribute::Revealed,
+                        (Some("open"), None) => EntityAttribute::Open,
+                        (Some("unlocked"), None) => EntityAttribute::Unlocked,
+                        (Some("durability"), Some(durability)) => {
+                            EntityAttribute::Durability(attribute_number(durability, "durability")?)
+                        }
+                        (Some("temporary"), Some(turns_left)) => {
+                            EntityAttribute::Temporary(attribute_number(turns_left, "temporary")?)
+                        }
+                        (Some("turns_per_spawn"), Some(turns_per_spawn)) => {
+                            EntityAttribute::TurnsPerSpawn(attribute_number(
+                                turns_per_spawn,
+                                "turns_per_spawn",
+                            )?)
+                        }
+                        _ => {
+                            return Err(line_error(&format!("Unknown attribute \"{}\"", attribute)))
+                        }
+                    };
+                    let kind_error = match entity_attribute {
+                        EntityAttribute::Health(_) if kind != MapEntityKind::Attackable => {
+                            Some("Only attackables have health")
+                        }
+                        EntityAttribute::Revealed if kind != MapEntityKind::Trap => {
+                            Some("Only traps can be revealed")
+                        }
+                        EntityAttribute::Open | EntityAttribute::Unlocked
+                            if kind != MapEntityKind::Door =>
+                        {
+                            Some("Only doors can be open or unlocked")
+                        }
+                        EntityAttribute::Durability(_) if kind != MapEntityKind::Wall => {
+                            Some("Only walls have durability")
+                        }
+                        EntityAttribute::TurnsPerSpawn(_) if kind != MapEntityKind::Spawner => {
+                            Some("Only spawners have turns_per_spawn")
+                        }
+                        _ => None,
+                    };
+                    if let Some(message) = kind_error {
+                        return Err(line_error(message));
+                    }
+                    entity_attributes.push(entity_attribute);
+                }
+                map_lines.push(MapLine::Entity {
+                    create_function,
+                    position,
+                    attributes: entity_attributes,
+                });
+            }
+            _ => return Err(line_error(&format!("Unknown keyword \"{}\"", keyword))),
+        }
+    }
+    Ok(map_lines)
+}
@@ -1,85 +1,273 @@
-use crate::data::{Attackable, Direction, Intangible, Position, AI};
+use crate::data::{
+    Attackable, CachedPath, Direction, Door, Footprint, Intangible, MovementDelay, OpensDoors,
+    Player, Position, Sprite, StatusKind, TerrainKind, AI,
+};
+use crate::doors::{door_at, open_door};
+use crate::status_effects::has_status;
+use crate::terrain::{apply_movement_cost, terrain_positions};
+use crate::traps::trigger_traps;
 use specs::{Entity, Join, World, WorldExt};
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 
+const PATHFIND_NODE_LIMIT: usize = 500;
+const DISTANCE_MAP_NODE_LIMIT: usize = 3000;
+const EXTRA_COST_THROUGH_AI: u32 = 5;
+const EXTRA_COST_THROUGH_TOXIC_MOSS: u32 = 4;
+
+/// Entities that open doors spend their move opening a closed door they walk into
+/// Sets off any traps the entity moves onto, which can kill it
+/// Slow terrain stops the entity from moving again for a while
 pub fn try_move(entity: Entity, direction: Direction, world: &mut World) -> Result<(), ()> {
+    if !world.is_alive(entity) {
+        return Err(());
+    }
     if can_move(entity, direction, world) {
-        let mut position_data = world.write_storage::<Position>();
-        let entity_position = position_data.get_mut(entity).unwrap();
-        *entity_position = entity_position.offset_by(direction);
+        {
+            let mut position_data = world.write_storage::<Position>();
+            let entity_position = position_data.get_mut(entity).unwrap();
+            *entity_position = entity_position.offset_by(direction);
+        }
+        trigger_traps(entity, world);
+        if world.is_alive(entity) {
+            apply_movement_cost(entity, world);
+        }
         Ok(())
     } else {
-        Err(())
+        let closed_door = {
+            let position_data = world.read_storage::<Position>();
+            let opens_doors_data = world.read_storage::<OpensDoors>();
+            let door_data = world.read_storage::<Door>();
+            let movement_delay_data = world.read_storage::<MovementDelay>();
+            if opens_doors_data.contains(entity)
+                && !has_status(entity, StatusKind::CantMove, world)
+                && !movement_delay_data.contains(entity)
+            {
+                door_at(
+                    position_data.get(entity).unwrap().offset_by(direction),
+                    world,
+                )
+                .filter(|door_entity| {
+                    let door = door_data.get(*door_entity).unwrap();
+                    !door.is_open && !door.is_locked
+                })
+            } else {
+                None
+            }
+        };
+        match closed_door {
+            Some(door_entity) => {
+                open_door(door_entity, world);
+                Ok(())
+            }
+            None => Err(()),
+        }
     }
 }
 
+/// Chasms can't be walked into, but unlike walls they don't block anything else
 pub fn can_move(entity: Entity, direction: Direction, world: &World) -> bool {
-    let attackable_data = world.read_storage::<Attackable>();
-    let entity_attackable = attackable_data.get(entity).unwrap();
-    if entity_attackable.cant_move_turns != 0 {
+    if has_status(entity, StatusKind::CantMove, world)
+        || world.read_storage::<MovementDelay>().contains(entity)
+    {
         return false;
     }
 
-    let obstacles;
-    let entity_position;
-    {
-        let position_data = world.read_storage::<Position>();
-        let intangible_data = world.read_storage::<Intangible>();
-        obstacles = (&position_data, !&intangible_data)
-            .join()
-            .map(|(position, _)| *position)
-            .collect::<HashSet<Position>>();
-        entity_position = *position_data.get(entity).unwrap();
+    let chasms = chasm_positions(world);
+
+    let entities = world.entities();
+    let position_data = world.read_storage::<Position>();
+    let intangible_data = world.read_storage::<Intangible>();
+    let footprint_data = world.read_storage::<Footprint>();
+    let obstacles = (
+        &entities,
+        &position_data,
+        !&intangible_data,
+        footprint_data.maybe(),
+    )
+        .join()
+        .filter(|(other_entity, _, _, _)| *other_entity != entity)
+        .flat_map(|(_, position, _, footprint)| position.footprint_positions(footprint))
+        .collect::<HashSet<Position>>();
+    let new_position = position_data.get(entity).unwrap().offset_by(direction);
+    new_position
+        .footprint_positions(footprint_data.get(entity))
+        .iter()
+        .all(|position| !obstacles.contains(position) && !chasms.contains(position))
+}
+
+/// Returns every position taken up by something that can't be walked through, including chasms
+pub fn obstacle_positions(world: &World) -> HashSet<Position> {
+    let position_data = world.read_storage::<Position>();
+    let intangible_data = world.read_storage::<Intangible>();
+    let footprint_data = world.read_storage::<Footprint>();
+    let mut obstacles = (&position_data, !&intangible_data, footprint_data.maybe())
+        .join()
+        .flat_map(|(position, _, footprint)| position.footprint_positions(footprint))
+        .collect::<HashSet<Position>>();
+    obstacles.extend(chasm_positions(world));
+    obstacles
+}
+
+fn chasm_positions(world: &World) -> HashSet<Position> {
+    terrain_positions(world)
+        .into_iter()
+        .filter(|(_, terrain_kind)| terrain_kind.movement_cost().is_none())
+        .map(|(position, _)| position)
+        .collect()
+}
+
+/// The extra cost of pathing onto a position on top of the step itself,
+/// or None if it can't be walked onto at all
+/// Toxic moss doesn't slow anything down, but it's still worth walking around
+fn extra_terrain_cost(terrain_kind: Option<&TerrainKind>) -> Option<u32> {
+    match terrain_kind {
+        None => Some(0),
+        Some(TerrainKind::ToxicMoss) => Some(EXTRA_COST_THROUGH_TOXIC_MOSS),
+        Some(terrain_kind) => terrain_kind.movement_cost().map(|cost| cost - 1),
     }
-    let new_position = entity_position.offset_by(direction);
-    !obstacles.contains(&new_position)
 }
 
-pub fn pathfind(start: Position, goal: Position, world: &mut World) -> Vec<Position> {
+/// Returns every floor position that isn't covered by a wall, whether or not something is standing on it
+pub fn floor_positions(world: &World) -> HashSet<Position> {
     let position_data = world.read_storage::<Position>();
     let intangible_data = world.read_storage::<Intangible>();
-    let ai_data = world.read_storage::<AI>();
-    let obstacles = (&position_data, !&intangible_data, !&ai_data)
+    let attackable_data = world.read_storage::<Attackable>();
+    let sprite_data = world.read_storage::<Sprite>();
+    let walls = (&position_data, !&intangible_data, !&attackable_data)
         .join()
         .map(|(position, _, _)| *position)
         .collect::<HashSet<Position>>();
+    (&position_data, &intangible_data, &sprite_data)
+        .join()
+        .map(|(position, _, _)| *position)
+        .filter(|position| !walls.contains(position))
+        .collect()
+}
+
+/// A* search for a path to a position orthogonally adjacent to the target
+/// Other AI can be pathed through, but at an extra cost so that they're usually walked around,
+/// and closed doors can be pathed through by entities that open doors
+/// Slow terrain costs more to path through and chasms can't be pathed through at all
+/// If the target can't be reached within the node limit, returns a path to the closest position found
+pub fn pathfind(moving_entity: Entity, target: Entity, world: &World) -> Vec<Position> {
+    let entities = world.entities();
+    let position_data = world.read_storage::<Position>();
+    let intangible_data = world.read_storage::<Intangible>();
+    let ai_data = world.read_storage::<AI>();
+    let footprint_data = world.read_storage::<Footprint>();
+    let door_data = world.read_storage::<Door>();
+    let opens_doors = world.read_storage::<OpensDoors>().contains(moving_entity);
+    let terrain = terrain_positions(world);
+    let mut obstacles = chasm_positions(world);
+    let mut ai_positions = HashSet::new();
+    for (entity, position, _, ai, footprint, door) in (
+        &entities,
+        &position_data,
+        !&intangible_data,
+        ai_data.maybe(),
+        footprint_data.maybe(),
+        door_data.maybe(),
+    )
+        .join()
+    {
+        let can_open = opens_doors && matches!(door, Some(door) if !door.is_locked);
+        if entity != moving_entity && !can_open {
+            for position in position.footprint_positions(footprint) {
+                if ai.is_some() {
+                    ai_positions.insert(position);
+                } else {
+                    obstacles.insert(position);
+                }
+            }
+        }
+    }
+
+    let start = *position_data.get(moving_entity).unwrap();
+    let moving_entity_footprint = footprint_data.get(moving_entity);
+    let goal = *position_data.get(target).unwrap();
+    let goal_positions = goal.footprint_positions(footprint_data.get(target));
+    let radii = moving_entity_footprint.map_or(0, |footprint| footprint.radius)
+        + footprint_data
+            .get(target)
+            .map_or(0, |footprint| footprint.radius);
+    let heuristic = |position: Position| {
+        position
+            .chebyshev_distance_from(goal)
+            .saturating_sub(1 + radii as u32)
+    };
+    let is_next_to_goal = |position: Position| {
+        position
+            .footprint_positions(moving_entity_footprint)
+            .iter()
+            .any(|position| {
+                goal_positions
+                    .iter()
+                    .any(|goal_position| position.distance_from(*goal_position) == 1)
+            })
+    };
     let mut frontier = BinaryHeap::new();
     let mut came_from = HashMap::new();
     let mut cost_so_far = HashMap::new();
-    frontier.push(FrontierNode::new(start, 0));
+    frontier.push(FrontierNode::new(start, heuristic(start)));
     cost_so_far.insert(start, 0u32);
 
     let mut last_node = None;
+    let mut closest_node = (heuristic(start), start);
+    let mut nodes_explored = 0;
     while let Some(visiting) = frontier.pop() {
-        if visiting.position.distance_from(goal) == 1 {
-            last_node = Some(visiting);
+        if is_next_to_goal(visiting.position) {
+            last_node = Some(visiting.position);
+            break;
+        }
+        nodes_explored += 1;
+        if nodes_explored > PATHFIND_NODE_LIMIT {
             break;
         }
         for next in visiting.position.neighbors(&obstacles) {
-            let new_cost = cost_so_far[&visiting.position] + 1;
+            let next_positions = next.footprint_positions(moving_entity_footprint);
+            if next_positions
+                .iter()
+                .any(|position| obstacles.contains(position))
+            {
+                continue;
+            }
+            let terrain_cost = next_positions
+                .iter()
+                .filter_map(|position| extra_terrain_cost(terrain.get(position)))
+                .max()
+                .unwrap_or(0);
+            let step_cost = if next_positions
+                .iter()
+                .any(|position| ai_positions.contains(position))
+            {
+                1 + EXTRA_COST_THROUGH_AI + terrain_cost
+            } else {
+                1 + terrain_cost
+            };
+            let new_cost = cost_so_far[&visiting.position] + step_cost;
             if !cost_so_far.contains_key(&next) || new_cost < cost_so_far[&next] {
                 cost_so_far.insert(next, new_cost);
-                frontier.push(FrontierNode::new(next, next.distance_from(goal)));
+                frontier.push(FrontierNode::new(next, new_cost + heuristic(next)));
                 came_from.insert(next, visiting.position);
+                if heuristic(next) < closest_node.0 {
+                    closest_node = (heuristic(next), next);
+                }
             }
         }
     }
 
-    if let Some(last_node) = last_node {
-        let mut path = Vec::with_capacity(start.distance_from(goal) as usize);
-        let mut last_node = last_node.position;
-        while let Some(next_node) = came_from.get(&last_node) {
-            path.push(last_node);
-            last_node = *next_node;
-        }
-        path.reverse();
-        return path;
+    let mut last_node = last_node.unwrap_or(closest_node.1);
+    let mut path = Vec::with_capacity(cost_so_far[&last_node] as usize);
+    while let Some(next_node) = came_from.get(&last_node) {
+        path.push(last_node);
+        last_node = *next_node;
     }
-
-    Vec::with_capacity(0)
+    path.reverse();
+    path
 }
 
+/// Reuses the path found on a previous turn as long as the target hasn't moved far from where it was
 pub fn try_move_towards(
     moving_entity: Entity,
     target: Entity,
@@ -92,27 +280,158 @@ pub fn try_move_towards(
             *position_data.get(target).unwrap(),
         )
     };
-    let path = pathfind(moving_entity_position, target_position, world);
-    if let Some(new_position) = path.get(0) {
-        let direction = match (
+
+    let cached_path = {
+        let mut cached_path_data = world.write_storage::<CachedPath>();
+        cached_path_data
+            .remove(moving_entity)
+            .filter(|cached_path| {
+                cached_path.goal.chebyshev_distance_from(target_position) <= 1
+                    && cached_path.path.len() >= 3
+                    && cached_path.path[0].chebyshev_distance_from(moving_entity_position) == 1
+            })
+    };
+    let (goal, mut path) = match cached_path {
+        Some(cached_path) => (cached_path.goal, cached_path.path),
+        None => (target_position, pathfind(moving_entity, target, world)),
+    };
+
+    if let Some(new_position) = path.first() {
+        let direction = Direction::from_offset(
             new_position.x - moving_entity_position.x,
             new_position.y - moving_entity_position.y,
-        ) {
-            (0, 1) => Direction::Up,
-            (0, -1) => Direction::Down,
-            (-1, 0) => Direction::Left,
-            (1, 0) => Direction::Right,
-            (-1, 1) => Direction::UpLeft,
-            (-1, -1) => Direction::DownLeft,
-            (1, -1) => Direction::DownRight,
-            (1, 1) => Direction::UpRight,
-            _ => unreachable!(),
-        };
-        try_move(moving_entity, direction, world)?
+        )
+        .unwrap();
+        try_move(moving_entity, direction, world)?;
+        if !world.is_alive(moving_entity) {
+            return Ok(());
+        }
+        path.remove(0);
+        let mut cached_path_data = world.write_storage::<CachedPath>();
+        cached_path_data
+            .insert(moving_entity, CachedPath { goal, path })
+            .unwrap();
     }
     Ok(())
 }
 
+/// Moves one step downhill on the shared DistanceMap towards the player,
+/// falling back to pathfinding when the entity is outside of the map or can open the doors the map goes around
+pub fn try_move_towards_player(moving_entity: Entity, world: &mut World) -> Result<(), ()> {
+    let (moving_entity_position, player_entity, opens_doors) = {
+        let position_data = world.read_storage::<Position>();
+        let player_data = world.read_storage::<Player>();
+        let entities = world.entities();
+        (
+            *position_data.get(moving_entity).unwrap(),
+            (&entities, &player_data).join().next().unwrap().0,
+            world.read_storage::<OpensDoors>().contains(moving_entity),
+        )
+    };
+    if opens_doors {
+        return try_move_towards(moving_entity, player_entity, world);
+    }
+
+    let mut directions = {
+        let distance_map = world.fetch::<DistanceMap>();
+        let current_distance = match distance_map.distance(moving_entity_position) {
+            Some(current_distance) => current_distance,
+            None => {
+                drop(distance_map);
+                return try_move_towards(moving_entity, player_entity, world);
+            }
+        };
+        [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+            Direction::UpLeft,
+            Direction::DownLeft,
+            Direction::DownRight,
+            Direction::UpRight,
+        ]
+        .iter()
+        .filter_map(|direction| {
+            distance_map
+                .distance(moving_entity_position.offset_by(*direction))
+                .filter(|distance| *distance < current_distance)
+                .map(|distance| (*direction, distance))
+        })
+        .collect::<Vec<(Direction, u32)>>()
+    };
+    directions.sort_by_key(|(_, distance)| *distance);
+
+    for (direction, _) in directions {
+        if try_move(moving_entity, direction, world).is_ok() {
+            return Ok(());
+        }
+    }
+    Err(())
+}
+
+/// Distances from every reachable position to the nearest position orthogonally adjacent to the target,
+/// computed once per turn and shared by every AI chasing the player
+/// Closed doors block the map like walls, and slow terrain counts as being further away
+#[derive(Debug, Clone)]
+pub struct DistanceMap {
+    distances: HashMap<Position, u32>,
+}
+
+impl DistanceMap {
+    pub fn new(target: Position, world: &World) -> Self {
+        let position_data = world.read_storage::<Position>();
+        let intangible_data = world.read_storage::<Intangible>();
+        let attackable_data = world.read_storage::<Attackable>();
+        let obstacles = (&position_data, !&intangible_data, !&attackable_data)
+            .join()
+            .map(|(position, _, _)| *position)
+            .chain(chasm_positions(world))
+            .collect::<HashSet<Position>>();
+        let terrain = terrain_positions(world);
+
+        let mut distances = HashMap::new();
+        let mut frontier = BinaryHeap::new();
+        for direction in &[
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            let position = target.offset_by(*direction);
+            if !obstacles.contains(&position) {
+                distances.insert(position, 0);
+                frontier.push(FrontierNode::new(position, 0));
+            }
+        }
+        while let Some(visiting) = frontier.pop() {
+            if distances.len() > DISTANCE_MAP_NODE_LIMIT {
+                break;
+            }
+            if visiting.priority > distances[&visiting.position] {
+                continue;
+            }
+            for next in visiting.position.neighbors(&obstacles) {
+                if next == target {
+                    continue;
+                }
+                let new_distance =
+                    visiting.priority + 1 + extra_terrain_cost(terrain.get(&next)).unwrap_or(0);
+                if !distances.contains_key(&next) || new_distance < distances[&next] {
+                    distances.insert(next, new_distance);
+                    frontier.push(FrontierNode::new(next, new_distance));
+                }
+            }
+        }
+
+        Self { distances }
+    }
+
+    pub fn distance(&self, position: Position) -> Option<u32> {
+        self.distances.get(&position).copied()
+    }
+}
+
 #[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
 struct FrontierNode {
     position: Position,
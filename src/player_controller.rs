@@ -1,6 +1,9 @@
 use crate::data::*;
+use crate::doors::{door_at, player_use_door, reveal_secret_walls};
 use crate::generate_dungeon::GenerateDungeonSystem;
 use crate::movement::try_move;
+use crate::traps::detect_traps;
+use rand::Rng;
 use specs::{Join, World, WorldExt};
 
 pub struct PlayerControllerSystem {
@@ -28,7 +31,12 @@ impl PlayerControllerSystem {
 
         let player_acted = match self.action {
             PlayerAction::None => false,
-            PlayerAction::Pass => true,
+            PlayerAction::Pass => {
+                let player_position = *world.read_storage::<Position>().get(player_entity).unwrap();
+                reveal_secret_walls(player_position, 1, world);
+                detect_traps(player_position, 1, world);
+                true
+            }
             PlayerAction::Interact => {
                 let mut end_turn = false;
 
@@ -68,6 +76,20 @@ impl PlayerControllerSystem {
                     }
                 };
 
+                let facing_door = {
+                    let position_data = world.read_storage::<Position>();
+                    let player_position = position_data.get(player_entity).unwrap();
+                    match player.facing_direction {
+                        Direction::Up | Direction::Down | Direction::Left | Direction::Right => {
+                            door_at(player_position.offset_by(player.facing_direction), world)
+                        }
+                        _ => None,
+                    }
+                };
+                if let Some(door_entity) = facing_door {
+                    end_turn = player_use_door(door_entity, world);
+                }
+
                 let is_facing_staircase = {
                     let position_data = world.read_storage::<Position>();
                     let staircase_data = world.read_storage::<Staircase>();
@@ -91,11 +113,12 @@ impl PlayerControllerSystem {
                         if player_attackable.current_health > player_attackable.max_health {
                             player_attackable.current_health = player_attackable.max_health;
                         }
-
-                        player_attackable.oozed_stacks = 0;
-                        player_attackable.cant_attack_turns = 0;
-                        player_attackable.cant_move_turns = 0;
-                        player_attackable.blight_stacks = 0;
+                    }
+                    if let Some(player_status_effects) = world
+                        .write_storage::<StatusEffects>()
+                        .get_mut(player_entity)
+                    {
+                        player_status_effects.cleanse(CleanseTag::Debuff);
                     }
                     generate_dungeon_system.run(world);
                     end_turn = true;
@@ -113,7 +136,20 @@ impl PlayerControllerSystem {
                     let mut player_data = world.write_storage::<Player>();
                     player_data.get_mut(player_entity).unwrap().facing_direction = direction;
                 }
+                let bumped_position = world
+                    .read_storage::<Position>()
+                    .get(player_entity)
+                    .unwrap()
+                    .offset_by(direction);
                 if try_move(player_entity, direction, world).is_ok() {
+                    let spots_nearby_traps = world.fetch_mut::<RNG>().0.gen_ratio(1, 4);
+                    if spots_nearby_traps && world.is_alive(player_entity) {
+                        let player_position =
+                            *world.read_storage::<Position>().get(player_entity).unwrap();
+                        detect_traps(player_position, 1, world);
+                    }
+                    true
+                } else if reveal_secret_walls(bumped_position, 0, world) != 0 {
                     true
                 } else {
                     self.action = PlayerAction::UseItem(ItemSlot::One);
@@ -121,18 +157,17 @@ impl PlayerControllerSystem {
                 }
             }
             PlayerAction::UseItem(item_slot) => {
-                let inventory_index = match item_slot {
-                    ItemSlot::One => 0,
-                    ItemSlot::Two => 1,
-                    ItemSlot::Three => 2,
-                    ItemSlot::Four => 3,
-                };
+                let inventory_index = item_slot.inventory_index();
                 if let Some(item_entity) = player.inventory[inventory_index] {
                     let item = {
                         let item_data = world.read_storage::<Item>();
                         *item_data.get(item_entity).unwrap()
                     };
                     let item_result = (item.try_use)(item_entity, world);
+                    {
+                        let mut player_data = world.write_storage::<Player>();
+                        player_data.get_mut(player_entity).unwrap().selected_target = None;
+                    }
                     if item_result.should_end_turn {
                         let mut player_data = world.write_storage::<Player>();
                         let player = player_data.get_mut(player_entity).unwrap();
@@ -176,6 +211,14 @@ impl PlayerControllerSystem {
     }
 }
 
+pub fn item_targeting(item_slot: ItemSlot, world: &World) -> Option<Targeting> {
+    let player_data = world.read_storage::<Player>();
+    let item_data = world.read_storage::<Item>();
+    let player = (&player_data).join().next().unwrap();
+    player.inventory[item_slot.inventory_index()]
+        .and_then(|item_entity| item_data.get(item_entity).unwrap().stats.targeting)
+}
+
 #[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
 pub enum PlayerAction {
     None,
@@ -1,4 +1,7 @@
+use crate::attack::player_get_targets;
 use crate::data::*;
+use crate::examine::{describe_item, describe_position};
+use crate::player_controller::item_targeting;
 use noise::{NoiseFn, OpenSimplex};
 use sdl2::image::LoadTexture;
 use sdl2::pixels::{Color, PixelFormatEnum};
@@ -9,6 +12,8 @@ use sdl2::Sdl;
 use specs::{Join, World, WorldExt};
 use std::time::Instant;
 
+pub const DAZZLED_VIEW_RADIUS: i16 = 2;
+
 pub struct RenderSystem {
     canvas: WindowCanvas,
     ttf_context: Sdl2TtfContext,
@@ -47,10 +52,19 @@ impl RenderSystem {
         let position_data = world.read_storage::<Position>();
         let sprite_data = world.read_storage::<Sprite>();
         let attackable_data = world.read_storage::<Attackable>();
+        let telegraph_data = world.read_storage::<Telegraph>();
+        let terrain_data = world.read_storage::<Terrain>();
+        let gas_data = world.read_storage::<Gas>();
+        let status_effects_data = world.read_storage::<StatusEffects>();
         let mut message_log = world.fetch_mut::<MessageLog>();
 
         let game_state = *world.fetch::<GameState>();
-        if let GameState::PlayerTurn | GameState::EnemyTurn | GameState::BagUI(_) = game_state {
+        if let GameState::PlayerTurn
+        | GameState::EnemyTurn
+        | GameState::BagUI(_)
+        | GameState::Examine(_, _)
+        | GameState::Targeting(_, _) = game_state
+        {
             if !cfg!(debug_assertions) {
                 let player_attackable = (&player_data, &attackable_data).join().next().unwrap().1;
                 let player_health_percentage =
@@ -86,7 +100,19 @@ impl RenderSystem {
                 self.canvas.copy(&texture, None, None).unwrap();
             }
 
-            let player_position = (&player_data, &position_data).join().next().unwrap().1;
+            let (player_entity, _, player_position) = (&entities, &player_data, &position_data)
+                .join()
+                .next()
+                .unwrap();
+            let view_radius = if status_effects_data
+                .get(player_entity)
+                .filter(|player_status_effects| player_status_effects.has(StatusKind::Dazzled))
+                .is_some()
+            {
+                DAZZLED_VIEW_RADIUS
+            } else {
+                7
+            };
             let mut render_objects = (&entities, &position_data, &sprite_data)
                 .join()
                 .filter_map(|(entity, position, sprite)| {
@@ -94,8 +120,8 @@ impl RenderSystem {
                         position.x - player_position.x + 7,
                         player_position.y - position.y + 7,
                     );
-                    if (0..15).contains(&adjusted_position.x)
-                        && (0..15).contains(&adjusted_position.y)
+                    if (7 - view_radius..=7 + view_radius).contains(&adjusted_position.x)
+                        && (7 - view_radius..=7 + view_radius).contains(&adjusted_position.y)
                     {
                         Some((entity, adjusted_position, sprite))
                     } else {
@@ -103,7 +129,15 @@ impl RenderSystem {
                     }
                 })
                 .collect::<Vec<_>>();
-            render_objects.sort_unstable_by_key(|(_, _, sprite)| sprite.in_foreground);
+            // Floors and terrain go under everything else in the background, like open doors, traps and fire,
+            // and gas goes over everything else in the foreground
+            render_objects.sort_unstable_by_key(|(entity, _, sprite)| {
+                (
+                    sprite.in_foreground,
+                    gas_data.contains(*entity),
+                    sprite.id != "floor" && !terrain_data.contains(*entity),
+                )
+            });
             for (entity, entity_position, entity_sprite) in render_objects {
                 let mut dest_rect = Rect::new(
                     (entity_position.x * 32) as i32,
@@ -114,10 +148,30 @@ impl RenderSystem {
                 if entity_sprite.double_sized {
                     dest_rect = Rect::new(dest_rect.x - 32, dest_rect.y - 32, 96, 96);
                 }
-                let texture = texture_creator
+                let mut texture = texture_creator
                     .load_texture(format!("assets/{}.png", entity_sprite.id))
                     .unwrap();
+                if let Some(gas) = gas_data.get(entity) {
+                    texture.set_alpha_mod((64 + gas.concentration.min(40) * 191 / 40) as u8);
+                }
                 self.canvas.copy(&texture, None, dest_rect).unwrap();
+                if let Some(telegraph) = telegraph_data.get(entity) {
+                    let surface = font
+                        .render(&telegraph.turns_left.to_string())
+                        .blended(Color::RGBA(255, 255, 255, 255))
+                        .unwrap();
+                    let texture = texture_creator
+                        .create_texture_from_surface(&surface)
+                        .unwrap();
+                    let texture_info = texture.query();
+                    let text_rect = Rect::new(
+                        dest_rect.x + (32 - texture_info.width as i32) / 2,
+                        dest_rect.y + (32 - texture_info.height as i32) / 2,
+                        texture_info.width,
+                        texture_info.height,
+                    );
+                    self.canvas.copy(&texture, None, text_rect).unwrap();
+                }
                 if let Some(player) = player_data.get(entity) {
                     let texture = match player.facing_direction {
                         Direction::Up | Direction::Down | Direction::Left | Direction::Right => {
@@ -143,6 +197,98 @@ impl RenderSystem {
                         .copy_ex(&texture, None, dest_rect, rotation, None, false, false)
                         .unwrap();
                 }
+                if let Some(entity_attackable) = attackable_data.get(entity) {
+                    if entity_sprite.id != "concealed" {
+                        if entity_attackable.current_health < entity_attackable.max_health
+                            && !entity_attackable.is_boss
+                        {
+                            let health_bar_width = (28.0 * entity_attackable.current_health as f64
+                                / entity_attackable.max_health as f64)
+                                .round() as u32;
+                            self.canvas.set_draw_color(Color::RGBA(60, 0, 0, 255));
+                            self.canvas
+                                .fill_rect(Rect::new(
+                                    dest_rect.x + 2,
+                                    dest_rect.bottom() - 4,
+                                    28,
+                                    3,
+                                ))
+                                .unwrap();
+                            if health_bar_width != 0 {
+                                self.canvas.set_draw_color(Color::RGBA(255, 0, 0, 255));
+                                self.canvas
+                                    .fill_rect(Rect::new(
+                                        dest_rect.x + 2,
+                                        dest_rect.bottom() - 4,
+                                        health_bar_width,
+                                        3,
+                                    ))
+                                    .unwrap();
+                            }
+                        }
+
+                        let status_pips = status_effects_data
+                            .get(entity)
+                            .map(|status_effects| status_effects.effects())
+                            .unwrap_or_default();
+                        for (i, effect) in status_pips.iter().enumerate() {
+                            let (r, g, b) = effect.kind.pip_color();
+                            self.canvas.set_draw_color(Color::RGBA(r, g, b, 255));
+                            self.canvas
+                                .fill_rect(Rect::new(
+                                    dest_rect.x + 2 + (5 * i as i32),
+                                    dest_rect.y + 1,
+                                    4,
+                                    4,
+                                ))
+                                .unwrap();
+                        }
+                        self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 255));
+                    }
+                }
+            }
+
+            let mut boss_health_bar_height = 0;
+            {
+                let name_data = world.read_storage::<Name>();
+                if let Some((boss_name, boss_attackable)) = (&name_data, &attackable_data)
+                    .join()
+                    .find(|(_, attackable)| attackable.is_boss)
+                {
+                    let surface = font
+                        .render(boss_name.get_text())
+                        .blended(Color::RGBA(255, 255, 255, 255))
+                        .unwrap();
+                    let texture = texture_creator
+                        .create_texture_from_surface(&surface)
+                        .unwrap();
+                    let texture_info = texture.query();
+                    let dest_rect = Rect::new(4, 4, texture_info.width, texture_info.height);
+                    self.canvas.fill_rect(dest_rect).unwrap();
+                    self.canvas.copy(&texture, None, dest_rect).unwrap();
+
+                    let health_bar_width = (300.0 * boss_attackable.current_health as f64
+                        / boss_attackable.max_health as f64)
+                        .round() as u32;
+                    let health_bar_y = 6 + texture_info.height as i32;
+                    self.canvas.set_draw_color(Color::RGBA(60, 0, 0, 255));
+                    self.canvas
+                        .fill_rect(Rect::new(4, health_bar_y, 300, 10))
+                        .unwrap();
+                    if health_bar_width != 0 {
+                        self.canvas
+                            .set_draw_color(if boss_attackable.is_invulnerable {
+                                Color::RGBA(140, 140, 140, 255)
+                            } else {
+                                Color::RGBA(255, 0, 0, 255)
+                            });
+                        self.canvas
+                            .fill_rect(Rect::new(4, health_bar_y, health_bar_width, 10))
+                            .unwrap();
+                    }
+                    self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 255));
+                    boss_health_bar_height = health_bar_y as u32 + 14;
+                }
             }
 
             {
@@ -201,7 +347,7 @@ impl RenderSystem {
                 self.canvas.copy(&texture, None, dest_rect).unwrap();
             }
 
-            let mut height_used = 0;
+            let mut height_used = boss_health_bar_height;
             for (index, message) in message_log.recent_messages().enumerate() {
                 let mut alpha = 255;
                 let time_since_message_creation = message.time_created.elapsed();
@@ -240,6 +386,66 @@ impl RenderSystem {
             }
         }
 
+        let examined_position = match game_state {
+            GameState::Examine(cursor_x, cursor_y) => {
+                let player_position = (&player_data, &position_data).join().next().unwrap().1;
+                Some((
+                    Position::new(
+                        player_position.x + cursor_x as i16 - 7,
+                        player_position.y - cursor_y as i16 + 7,
+                    ),
+                    "assets/ui_examine_cursor.png",
+                ))
+            }
+            GameState::Targeting(item_slot, selected_target_index) => {
+                item_targeting(item_slot, world)
+                    .and_then(|targeting| {
+                        player_get_targets(targeting, world)
+                            .get(selected_target_index)
+                            .copied()
+                    })
+                    .map(|target_entity| {
+                        (
+                            *position_data.get(target_entity).unwrap(),
+                            "assets/ui_target_cursor.png",
+                        )
+                    })
+            }
+            _ => None,
+        };
+        if let Some((examined_position, cursor_texture)) = examined_position {
+            let player_position = (&player_data, &position_data).join().next().unwrap().1;
+            let dest_rect = Rect::new(
+                (examined_position.x - player_position.x + 7) as i32 * 32,
+                (player_position.y - examined_position.y + 7) as i32 * 32,
+                32,
+                32,
+            );
+            let texture = texture_creator.load_texture(cursor_texture).unwrap();
+            self.canvas.copy(&texture, None, dest_rect).unwrap();
+
+            let mut height_used = 0;
+            for line in describe_position(examined_position, world).iter().rev() {
+                let surface = font
+                    .render(line)
+                    .blended(Color::RGBA(255, 255, 255, 255))
+                    .unwrap();
+                let texture = texture_creator
+                    .create_texture_from_surface(&surface)
+                    .unwrap();
+                let texture_info = texture.query();
+                height_used += texture_info.height;
+                let dest_rect = Rect::new(
+                    4,
+                    480 - 4 - height_used as i32,
+                    texture_info.width,
+                    texture_info.height,
+                );
+                self.canvas.fill_rect(dest_rect).unwrap();
+                self.canvas.copy(&texture, None, dest_rect).unwrap();
+            }
+        }
+
         if let GameState::BagUI(bag_ui_state) = game_state {
             match bag_ui_state {
                 BagUIState::Overview(selected_item_x, selected_item_y)
@@ -312,6 +518,38 @@ impl RenderSystem {
                         .load_texture("assets/ui_item_frame_selected.png")
                         .unwrap();
                     self.canvas.copy(&texture, None, dest_rect).unwrap();
+
+                    if let Some(item_entity) = player.inventory[index] {
+                        let mut height_used = 0;
+                        for (line_index, line) in
+                            describe_item(item_entity, world).iter().enumerate()
+                        {
+                            let surface = font
+                                .render(line)
+                                .blended_wrapped(
+                                    if line_index == 0 {
+                                        Color::RGBA(255, 255, 255, 255)
+                                    } else {
+                                        Color::RGBA(200, 200, 200, 255)
+                                    },
+                                    400,
+                                )
+                                .unwrap();
+                            let texture = texture_creator
+                                .create_texture_from_surface(&surface)
+                                .unwrap();
+                            let texture_info = texture.query();
+                            let dest_rect = Rect::new(
+                                40,
+                                292 + height_used as i32,
+                                texture_info.width,
+                                texture_info.height,
+                            );
+                            self.canvas.fill_rect(dest_rect).unwrap();
+                            self.canvas.copy(&texture, None, dest_rect).unwrap();
+                            height_used += texture_info.height;
+                        }
+                    }
                 }
             }
             if let BagUIState::ItemMenu(selected_item_x, selected_item_y, item_menu_option) =
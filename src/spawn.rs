@@ -1,21 +1,14 @@
-use crate::data::{Intangible, Position, Rarity, Spawner, Sprite, RNG};
+use crate::data::{Position, Rarity, Spawner, Sprite, RNG};
 use crate::entities::create_random_layer1;
+use crate::movement::obstacle_positions;
 use rand::seq::SliceRandom;
 use specs::{Join, World, WorldExt};
-use std::collections::HashSet;
 
 pub fn tick_spawners(world: &mut World) {
     let mut spawn_positions = Vec::new();
     let mut obstacles;
     {
-        obstacles = {
-            let position_data = world.read_storage::<Position>();
-            let intangible_data = world.read_storage::<Intangible>();
-            (&position_data, !&intangible_data)
-                .join()
-                .map(|(position, _)| *position)
-                .collect::<HashSet<Position>>()
-        };
+        obstacles = obstacle_positions(world);
 
         let mut spawner_data = world.write_storage::<Spawner>();
         let position_data = world.read_storage::<Position>();
@@ -0,0 +1,103 @@
+use crate::attack::damage;
+use crate::data::*;
+use specs::{Entity, World, WorldExt};
+
+/// Gives the entity StatusEffects the first time anything is applied to it
+pub fn apply_status(entity: Entity, effect: StatusEffect, stacking: Stacking, world: &mut World) {
+    let mut status_effects_data = world.write_storage::<StatusEffects>();
+    if let Ok(entry) = status_effects_data.entry(entity) {
+        entry
+            .or_insert_with(StatusEffects::default)
+            .apply(effect, stacking);
+    }
+}
+
+pub fn has_status(entity: Entity, kind: StatusKind, world: &World) -> bool {
+    world
+        .read_storage::<StatusEffects>()
+        .get(entity)
+        .filter(|status_effects| status_effects.has(kind))
+        .is_some()
+}
+
+pub fn status_stacks(entity: Entity, kind: StatusKind, world: &World) -> u32 {
+    world
+        .read_storage::<StatusEffects>()
+        .get(entity)
+        .map_or(0, |status_effects| status_effects.stacks(kind))
+}
+
+pub fn cleanse_status(entity: Entity, tag: CleanseTag, world: &mut World) {
+    if let Some(status_effects) = world.write_storage::<StatusEffects>().get_mut(entity) {
+        status_effects.cleanse(tag);
+    }
+}
+
+/// Counts down every effect on the entity, then runs the per turn hooks of the ones that were active
+pub fn tick_status_effects(entity: Entity, world: &mut World) {
+    let active_effects = world
+        .write_storage::<StatusEffects>()
+        .get_mut(entity)
+        .map(|status_effects| status_effects.tick())
+        .unwrap_or_default();
+    for effect in active_effects {
+        // An earlier effect could have killed it
+        if !world.is_alive(entity) {
+            return;
+        }
+        if let Some(on_tick) = on_tick(effect.kind) {
+            (on_tick)(entity, effect, world);
+        }
+    }
+}
+
+fn on_tick(kind: StatusKind) -> Option<fn(Entity, StatusEffect, &mut World)> {
+    match kind {
+        StatusKind::Blight => Some(|entity, _, world| {
+            damage_over_time(
+                1,
+                DamageType::Blight,
+                "blight",
+                MessageColor::Green,
+                entity,
+                world,
+            );
+        }),
+        StatusKind::Burning => Some(|entity, effect, world| {
+            damage_over_time(
+                effect.stacks,
+                DamageType::Fire,
+                "burning",
+                MessageColor::Orange,
+                entity,
+                world,
+            );
+        }),
+        StatusKind::Oozed | StatusKind::CantMove | StatusKind::CantAttack | StatusKind::Dazzled => {
+            None
+        }
+    }
+}
+
+fn damage_over_time(
+    amount: u32,
+    damage_type: DamageType,
+    cause: &str,
+    color: MessageColor,
+    entity: Entity,
+    world: &mut World,
+) {
+    let entity_name = *world.read_storage::<Name>().get(entity).unwrap();
+    let (_, damage_dealt, resistance) = damage(amount, false, damage_type, None, entity, world);
+    world.fetch_mut::<MessageLog>().new_message(
+        format!(
+            "{} took {} damage from {}{}",
+            entity_name.get_text(),
+            damage_dealt,
+            cause,
+            resistance.map_or("", Resistance::describe),
+        ),
+        color,
+        MessageDisplayLength::Short,
+    );
+}
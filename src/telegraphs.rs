@@ -0,0 +1,109 @@
+use crate::attack::damage;
+use crate::data::*;
+use crate::entities::create_telegraph;
+use crate::movement::floor_positions;
+use crate::walls::damage_walls;
+use specs::{Entity, Join, World, WorldExt};
+use std::collections::HashSet;
+
+/// Places telegraphs on every position that can be walked on
+pub fn telegraph_area(
+    positions: &[Position],
+    turns_left: u32,
+    damage: u32,
+    damage_type: DamageType,
+    source: Entity,
+    world: &mut World,
+) {
+    let walkable_positions = floor_positions(world);
+    let positions = positions
+        .iter()
+        .filter(|position| walkable_positions.contains(position))
+        .copied()
+        .collect::<HashSet<Position>>();
+    for position in positions {
+        create_telegraph(
+            position,
+            Telegraph::new(turns_left, damage, damage_type, source),
+            world,
+        );
+    }
+}
+
+pub fn tick_telegraphs(world: &mut World) {
+    let mut erupting_telegraphs = Vec::new();
+    {
+        let entities = world.entities();
+        let mut telegraph_data = world.write_storage::<Telegraph>();
+        let position_data = world.read_storage::<Position>();
+        for (entity, telegraph, position) in (&entities, &mut telegraph_data, &position_data).join()
+        {
+            telegraph.turns_left = telegraph.turns_left.saturating_sub(1);
+            if telegraph.turns_left == 0 {
+                erupting_telegraphs.push((entity, *telegraph, *position));
+            }
+        }
+    }
+
+    let mut already_hit = HashSet::new();
+    for (telegraph_entity, telegraph, telegraph_position) in erupting_telegraphs {
+        let targets = {
+            let entities = world.entities();
+            let position_data = world.read_storage::<Position>();
+            let attackable_data = world.read_storage::<Attackable>();
+            let footprint_data = world.read_storage::<Footprint>();
+            (
+                &entities,
+                &position_data,
+                &attackable_data,
+                footprint_data.maybe(),
+            )
+                .join()
+                .filter(|(entity, position, _, footprint)| {
+                    *entity != telegraph.source
+                        && position
+                            .footprint_positions(*footprint)
+                            .contains(&telegraph_position)
+                })
+                .map(|(entity, _, _, _)| entity)
+                .collect::<Vec<Entity>>()
+        };
+        for target in targets {
+            if already_hit.insert(target) && world.is_alive(target) {
+                let target_name = *world.read_storage::<Name>().get(target).unwrap();
+                let (_, damage_dealt, resistance) = damage(
+                    telegraph.damage,
+                    false,
+                    telegraph.damage_type,
+                    None,
+                    target,
+                    world,
+                );
+                world.fetch_mut::<MessageLog>().new_message(
+                    format!(
+                        "{} was caught in the blast for {} damage{}",
+                        target_name.get_text(),
+                        damage_dealt,
+                        resistance.map_or("", Resistance::describe),
+                    ),
+                    MessageColor::Orange,
+                    MessageDisplayLength::Short,
+                );
+            }
+        }
+        // Bosses hit hard enough to break the walls around them
+        let source_is_boss = world
+            .read_storage::<Attackable>()
+            .get(telegraph.source)
+            .map(|source_attackable| source_attackable.is_boss)
+            == Some(true);
+        if source_is_boss {
+            damage_walls(
+                &telegraph_position.neighbors(&HashSet::new()),
+                telegraph.damage,
+                world,
+            );
+        }
+        world.delete_entity(telegraph_entity).unwrap();
+    }
+}
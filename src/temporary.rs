@@ -0,0 +1,19 @@
+use crate::data::Temporary;
+use specs::{Join, World, WorldExt};
+
+pub fn tick_temporaries(world: &mut World) {
+    let mut expired_entities = Vec::new();
+    {
+        let entities = world.entities();
+        let mut temporary_data = world.write_storage::<Temporary>();
+        for (entity, temporary) in (&entities, &mut temporary_data).join() {
+            temporary.turns_left = temporary.turns_left.saturating_sub(1);
+            if temporary.turns_left == 0 {
+                expired_entities.push(entity);
+            }
+        }
+    }
+    for entity in expired_entities {
+        world.delete_entity(entity).unwrap();
+    }
+}
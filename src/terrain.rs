@@ -0,0 +1,89 @@
+use crate::data::*;
+use crate::status_effects::{apply_status, cleanse_status};
+use specs::{Entity, Join, World, WorldExt};
+use std::collections::HashMap;
+
+const TOXIC_MOSS_BLIGHT_TURNS: u32 = 2;
+
+pub fn terrain_positions(world: &World) -> HashMap<Position, TerrainKind> {
+    let position_data = world.read_storage::<Position>();
+    let terrain_data = world.read_storage::<Terrain>();
+    (&position_data, &terrain_data)
+        .join()
+        .map(|(position, terrain)| (*position, terrain.kind))
+        .collect()
+}
+
+/// Returns every kind of terrain under the entity, including under the rest of its footprint
+pub fn terrain_under(
+    entity: Entity,
+    terrain_positions: &HashMap<Position, TerrainKind>,
+    world: &World,
+) -> Vec<TerrainKind> {
+    let position_data = world.read_storage::<Position>();
+    let footprint_data = world.read_storage::<Footprint>();
+    position_data
+        .get(entity)
+        .unwrap()
+        .footprint_positions(footprint_data.get(entity))
+        .iter()
+        .filter_map(|position| terrain_positions.get(position).copied())
+        .collect()
+}
+
+/// Stops the entity from moving again until it's made its way through the terrain it just entered
+pub fn apply_movement_cost(entity: Entity, world: &mut World) {
+    let movement_cost = terrain_under(entity, &terrain_positions(world), world)
+        .iter()
+        .filter_map(|terrain_kind| terrain_kind.movement_cost())
+        .max()
+        .unwrap_or(1);
+    if movement_cost > 1 {
+        // The first tick comes at the end of the round it moved in, so it's stuck for movement_cost - 1 of its turns
+        world
+            .write_storage::<MovementDelay>()
+            .insert(
+                entity,
+                MovementDelay {
+                    turns_left: movement_cost,
+                },
+            )
+            .unwrap();
+    }
+}
+
+pub fn tick_movement_delays(world: &mut World) {
+    let entities = world.entities();
+    let mut movement_delay_data = world.write_storage::<MovementDelay>();
+    let mut finished_entities = Vec::new();
+    for (entity, movement_delay) in (&entities, &mut movement_delay_data).join() {
+        movement_delay.turns_left = movement_delay.turns_left.saturating_sub(1);
+        if movement_delay.turns_left == 0 {
+            finished_entities.push(entity);
+        }
+    }
+    for entity in finished_entities {
+        movement_delay_data.remove(entity);
+    }
+}
+
+/// Applies the effects of the terrain the entity is standing on at the end of a turn,
+/// water puts out anything that's burning
+pub fn apply_standing_effects(
+    entity: Entity,
+    terrain_positions: &HashMap<Position, TerrainKind>,
+    world: &mut World,
+) {
+    let terrain_kinds = terrain_under(entity, terrain_positions, world);
+    if terrain_kinds.contains(&TerrainKind::ToxicMoss) {
+        apply_status(
+            entity,
+            StatusEffect::new(StatusKind::Blight, 1, Some(TOXIC_MOSS_BLIGHT_TURNS)),
+            Stacking::Refresh,
+            world,
+        );
+    }
+    if terrain_kinds.contains(&TerrainKind::ShallowWater) {
+        cleanse_status(entity, CleanseTag::Fire, world);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::attack::damage;
+use crate::data::*;
+use crate::gas::release_gas;
+use crate::movement::{floor_positions, obstacle_positions};
+use crate::spawn::tick_spawners;
+use crate::status_effects::apply_status;
+use rand::seq::IteratorRandom;
+use specs::{Entity, Join, World, WorldExt};
+
+const SPIKE_TRAP_DAMAGE: u32 = 6;
+const SNARE_TRAP_TURNS: u32 = 3;
+const SLEEP_GAS_TRAP_CONCENTRATION: u32 = 200;
+
+fn trap_sprite_id(trap_kind: TrapKind) -> &'static str {
+    match trap_kind {
+        TrapKind::Spike => "spike_trap",
+        TrapKind::Snare => "snare_trap",
+        TrapKind::Alarm => "alarm_trap",
+        TrapKind::Teleport => "teleport_trap",
+        TrapKind::SleepGas => "sleep_gas_trap",
+    }
+}
+
+/// Sets off every trap under the entity, called whenever something moves
+pub fn trigger_traps(entity: Entity, world: &mut World) {
+    let triggered_traps = {
+        let entities = world.entities();
+        let position_data = world.read_storage::<Position>();
+        let footprint_data = world.read_storage::<Footprint>();
+        let trap_data = world.read_storage::<Trap>();
+        let entity_positions = position_data
+            .get(entity)
+            .unwrap()
+            .footprint_positions(footprint_data.get(entity));
+        (&entities, &position_data, &trap_data)
+            .join()
+            .filter(|(_, trap_position, _)| entity_positions.contains(trap_position))
+            .map(|(trap_entity, _, trap)| (trap_entity, trap.kind))
+            .collect::<Vec<(Entity, TrapKind)>>()
+    };
+
+    for (trap_entity, trap_kind) in triggered_traps {
+        if !world.is_alive(entity) {
+            return;
+        }
+        reveal_trap(trap_entity, world);
+        {
+            let name_data = world.read_storage::<Name>();
+            world.fetch_mut::<MessageLog>().new_message(
+                format!(
+                    "{} stepped on the {}!",
+                    name_data.get(entity).unwrap().get_text(),
+                    name_data.get(trap_entity).unwrap().get_text()
+                ),
+                MessageColor::Orange,
+                MessageDisplayLength::Medium,
+            );
+        }
+
+        match trap_kind {
+            TrapKind::Spike => {
+                damage(
+                    SPIKE_TRAP_DAMAGE,
+                    false,
+                    DamageType::Physical,
+                    None,
+                    entity,
+                    world,
+                );
+            }
+            TrapKind::Snare => {
+                apply_status(
+                    entity,
+                    StatusEffect::new(StatusKind::CantMove, 1, Some(SNARE_TRAP_TURNS))
+                        .with_source(trap_entity),
+                    Stacking::Refresh,
+                    world,
+                );
+            }
+            TrapKind::Alarm => {
+                {
+                    let mut spawner_data = world.write_storage::<Spawner>();
+                    for spawner in (&mut spawner_data).join() {
+                        spawner.trigger();
+                    }
+                }
+                tick_spawners(world);
+                world.delete_entity(trap_entity).unwrap();
+            }
+            TrapKind::SleepGas => {
+                let trap_position = *world.read_storage::<Position>().get(trap_entity).unwrap();
+                release_gas(
+                    GasKind::Sleep,
+                    trap_position,
+                    SLEEP_GAS_TRAP_CONCENTRATION,
+                    world,
+                );
+                world.delete_entity(trap_entity).unwrap();
+            }
+            TrapKind::Teleport => {
+                // Entities bigger than one tile are too heavy to be teleported
+                if world.read_storage::<Footprint>().contains(entity) {
+                    continue;
+                }
+                let destination = {
+                    let obstacles = obstacle_positions(world);
+                    let trap_data = world.read_storage::<Trap>();
+                    let position_data = world.read_storage::<Position>();
+                    let trap_positions = (&position_data, &trap_data)
+                        .join()
+                        .map(|(position, _)| *position)
+                        .collect::<Vec<Position>>();
+                    let rng = &mut world.fetch_mut::<RNG>().0;
+                    floor_positions(world)
+                        .into_iter()
+                        .filter(|position| {
+                            !obstacles.contains(position) && !trap_positions.contains(position)
+                        })
+                        .choose(rng)
+                };
+                if let Some(destination) = destination {
+                    world
+                        .write_storage::<Position>()
+                        .insert(entity, destination)
+                        .unwrap();
+                    world.write_storage::<CachedPath>().remove(entity);
+                }
+            }
+        }
+    }
+}
+
+/// Reveals every hidden trap within the radius, returning how many were found
+pub fn detect_traps(center: Position, radius: u32, world: &mut World) -> u32 {
+    let detected_traps = {
+        let entities = world.entities();
+        let position_data = world.read_storage::<Position>();
+        let trap_data = world.read_storage::<Trap>();
+        (&entities, &position_data, &trap_data)
+            .join()
+            .filter(|(_, position, trap)| {
+                trap.is_hidden && position.chebyshev_distance_from(center) <= radius
+            })
+            .map(|(entity, _, _)| entity)
+            .collect::<Vec<Entity>>()
+    };
+    for trap_entity in &detected_traps {
+        reveal_trap(*trap_entity, world);
+        let name_data = world.read_storage::<Name>();
+        world.fetch_mut::<MessageLog>().new_message(
+            format!(
+                "You spotted a trap: {}",
+                name_data.get(*trap_entity).unwrap().get_text()
+            ),
+            MessageColor::White,
+            MessageDisplayLength::Medium,
+        );
+    }
+    detected_traps.len() as u32
+}
+
+pub fn reveal_trap(trap_entity: Entity, world: &mut World) {
+    let mut trap_data = world.write_storage::<Trap>();
+    let mut sprite_data = world.write_storage::<Sprite>();
+    let trap = trap_data.get_mut(trap_entity).unwrap();
+    if trap.is_hidden {
+        trap.is_hidden = false;
+        sprite_data
+            .insert(
+                trap_entity,
+                Sprite {
+                    id: trap_sprite_id(trap.kind),
+                    double_sized: false,
+                    in_foreground: false,
+                },
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,143 @@
+use crate::attack::damage;
+use crate::data::*;
+use crate::fire::ignite;
+use crate::walls::damage_walls;
+use specs::{Entity, Join, World, WorldExt};
+
+/// Runs every trigger the entity has for the event, in the order they were added
+pub fn run_triggers(event: TriggerEvent, entity: Entity, hit: &mut Hit, world: &mut World) {
+    let triggers = match world.read_storage::<Triggers>().get(entity) {
+        Some(triggers) => triggers.for_event(event),
+        None => return,
+    };
+    for trigger in triggers {
+        // An earlier trigger could have removed it
+        if !world.is_alive(entity) {
+            return;
+        }
+        (trigger.run)(hit, world);
+    }
+}
+
+/// Oozing attackers hit harder for every stack of ooze on the target, and add another stack with each melee hit
+pub fn ooze_triggers() -> Vec<Trigger> {
+    vec![
+        Trigger::new(TriggerEvent::Attack, |hit, world| {
+            let status_effects_data = world.read_storage::<StatusEffects>();
+            if let Some(target_status_effects) = status_effects_data.get(hit.target) {
+                hit.damage += target_status_effects.stacks(StatusKind::Oozed);
+            }
+        }),
+        Trigger::new(TriggerEvent::Hit, |hit, world| {
+            if hit.is_melee {
+                let mut status_effects_data = world.write_storage::<StatusEffects>();
+                let target_status_effects = status_effects_data
+                    .entry(hit.target)
+                    .unwrap()
+                    .or_insert_with(StatusEffects::default);
+                let mut effect = StatusEffect::new(StatusKind::Oozed, 1, None);
+                if let Some(attacker) = hit.attacker {
+                    effect = effect.with_source(attacker);
+                }
+                target_status_effects.apply(effect, Stacking::Add);
+            }
+        })
+        .with_description("Oozes on whatever it hits"),
+    ]
+}
+
+/// Spawners speed up every time the entity is struck while already below the health threshold
+pub fn lower_spawn_times(entity: Entity, health_threshold: f32, by_turns: u32, world: &mut World) {
+    let entity_health_percentage = {
+        let attackable_data = world.read_storage::<Attackable>();
+        let entity_attackable = attackable_data.get(entity).unwrap();
+        entity_attackable.current_health as f32 / entity_attackable.max_health as f32
+    };
+    if entity_health_percentage <= health_threshold {
+        let mut spawner_data = world.write_storage::<Spawner>();
+        for spawner in (&mut spawner_data).join() {
+            // Spawners that are already faster than the limit, like Ume's, are left alone
+            if spawner.turns_per_spawn > 10 {
+                spawner.turns_per_spawn = spawner.turns_per_spawn.saturating_sub(by_turns).max(10);
+            }
+        }
+        let mut message_log = world.fetch_mut::<MessageLog>();
+        message_log.new_message(
+            "The air around you feels more... dangerous...",
+            MessageColor::White,
+            MessageDisplayLength::Medium,
+        );
+    }
+}
+
+/// Hits everything within the radius of the entity, breaking walls and starting fires
+pub fn explode(entity: Entity, blast_damage: u32, blast_radius: u32, world: &mut World) {
+    {
+        let mut message_log = world.fetch_mut::<MessageLog>();
+        let name_data = world.read_storage::<Name>();
+        let entity_name = name_data.get(entity).unwrap();
+        message_log.new_message(
+            format!("{} exploded!", entity_name.get_text()),
+            MessageColor::White,
+            MessageDisplayLength::Medium,
+        );
+    }
+
+    let (targets, blast_area) = {
+        let attackable_data = world.read_storage::<Attackable>();
+        let position_data = world.read_storage::<Position>();
+        let footprint_data = world.read_storage::<Footprint>();
+        let entities = world.entities();
+        let blast_positions = position_data
+            .get(entity)
+            .unwrap()
+            .footprint_positions(footprint_data.get(entity));
+        let blast_area = blast_positions
+            .iter()
+            .flat_map(|blast_position| {
+                let radius = blast_radius as i16;
+                (-radius..=radius).flat_map(move |x_offset| {
+                    (-radius..=radius).map(move |y_offset| {
+                        Position::new(blast_position.x + x_offset, blast_position.y + y_offset)
+                    })
+                })
+            })
+            .collect::<Vec<Position>>();
+        let targets = (
+            &entities,
+            &position_data,
+            &attackable_data,
+            footprint_data.maybe(),
+        )
+            .join()
+            .filter(|(_, position, _, footprint)| {
+                position
+                    .footprint_positions(*footprint)
+                    .iter()
+                    .any(|position| {
+                        blast_positions.iter().any(|blast_position| {
+                            position.chebyshev_distance_from(*blast_position) <= blast_radius
+                        })
+                    })
+            })
+            .map(|(target, _, _, _)| target)
+            .collect::<Vec<Entity>>();
+        (targets, blast_area)
+    };
+    for target in targets {
+        // Anything that blew up along the way is already gone
+        // Every target counts as its own attacker, so the blast doesn't run the exploding entity's triggers
+        if world.is_alive(target) {
+            damage(
+                blast_damage,
+                false,
+                DamageType::Explosion,
+                Some(target),
+                target,
+                world,
+            );
+        }
+    }
+    damage_walls(&blast_area, blast_damage, world);
+    ignite(&blast_area, world);
+}
@@ -0,0 +1,78 @@
+use crate::data::*;
+use crate::entities::{create_terrain, create_wall_with_sprite};
+use crate::movement::DistanceMap;
+use specs::{Entity, Join, World, WorldExt};
+use std::collections::HashSet;
+
+/// Damages every breakable wall on the positions, crumbling the ones that run out of durability into rubble
+pub fn damage_walls(positions: &[Position], damage: u32, world: &mut World) {
+    let broken_walls = {
+        let entities = world.entities();
+        let position_data = world.read_storage::<Position>();
+        let mut breakable_data = world.write_storage::<Breakable>();
+        let mut sprite_data = world.write_storage::<Sprite>();
+        let mut broken_walls = Vec::new();
+        for (entity, position, breakable, sprite) in (
+            &entities,
+            &position_data,
+            &mut breakable_data,
+            &mut sprite_data,
+        )
+            .join()
+        {
+            if positions.contains(position) {
+                breakable.durability = breakable.durability.saturating_sub(damage);
+                if breakable.durability == 0 {
+                    broken_walls.push(entity);
+                } else if breakable.durability <= breakable.max_durability / 2 {
+                    sprite.id = "wall_cracked";
+                }
+            }
+        }
+        broken_walls
+    };
+
+    if !broken_walls.is_empty() {
+        for wall_entity in broken_walls {
+            break_wall(wall_entity, world);
+        }
+        world.fetch_mut::<MessageLog>().new_message(
+            "The wall crumbled into rubble",
+            MessageColor::White,
+            MessageDisplayLength::Short,
+        );
+        refresh_paths(world);
+    }
+}
+
+/// Replaces the wall with rubble, walling in anything that was on the other side of it so the floor stays enclosed
+fn break_wall(wall_entity: Entity, world: &mut World) {
+    let wall_position = *world.read_storage::<Position>().get(wall_entity).unwrap();
+    world.delete_entity(wall_entity).unwrap();
+    create_terrain(TerrainKind::Rubble, wall_position, world);
+
+    let occupied_positions = {
+        let position_data = world.read_storage::<Position>();
+        (&position_data)
+            .join()
+            .copied()
+            .collect::<HashSet<Position>>()
+    };
+    for position in wall_position.neighbors(&occupied_positions) {
+        create_wall_with_sprite(position, "wall", world);
+    }
+}
+
+/// Throws away every path found before the walls changed, so the way through the rubble gets used straight away
+fn refresh_paths(world: &mut World) {
+    world.write_storage::<CachedPath>().clear();
+    if world.try_fetch::<DistanceMap>().is_some() {
+        let player_position = {
+            let player_data = world.read_storage::<Player>();
+            let position_data = world.read_storage::<Position>();
+            *(&player_data, &position_data).join().next().unwrap().1
+        };
+        let distance_map = DistanceMap::new(player_position, world);
+        world.insert(distance_map);
+    }
+}